@@ -0,0 +1,440 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+use crate::metrics::histogram::{
+    backup_path, check_journal, journal_path, open_database, read_configuration, read_store,
+    HistogramKind, HistogramMap, RollupTier, STORE_BACKUPS,
+};
+use crate::util::{parse_duration, LockError, Lockfile};
+use byte_unit::{Byte, UnitType};
+use chrono::{DateTime, Local};
+use gumdrop::Options;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[derive(Options)]
+pub struct DbOptions {
+    #[options()]
+    help: bool,
+
+    /// Database to work on, defaults to the one zenith records to.
+    #[options(no_short, meta = "STRING")]
+    db: Option<String>,
+
+    #[options(command)]
+    command: Option<DbCommand>,
+}
+
+#[derive(Options)]
+enum DbCommand {
+    /// Print the tick, recorded series and time span.
+    Info(InfoOptions),
+    /// Check that the store, its backups and the journal read back intact.
+    Verify(VerifyOptions),
+    /// Fold the journal into the store.
+    Compact(CompactOptions),
+    /// Remove history older than a duration.
+    Prune(PruneOptions),
+    /// Remove series, by default those of disks and network interfaces that no longer exist.
+    DropSeries(DropSeriesOptions),
+}
+
+#[derive(Options)]
+struct InfoOptions {
+    #[options()]
+    help: bool,
+}
+
+#[derive(Options)]
+struct VerifyOptions {
+    #[options()]
+    help: bool,
+}
+
+#[derive(Options)]
+struct CompactOptions {
+    #[options()]
+    help: bool,
+}
+
+#[derive(Options)]
+struct PruneOptions {
+    #[options()]
+    help: bool,
+
+    /// Remove samples older than this, e.g. 30d.
+    #[options(
+        no_short,
+        long = "older-than",
+        required,
+        parse(try_from_str = "parse_duration"),
+        meta = "DURATION"
+    )]
+    older_than: Duration,
+}
+
+#[derive(Options)]
+struct DropSeriesOptions {
+    #[options()]
+    help: bool,
+
+    /// Only print the series that would be removed.
+    #[options(short = "n", long = "dry-run")]
+    dry_run: bool,
+
+    /// Series to remove, e.g. io_read:/dev/sdb. Defaults to disks that no longer exist.
+    #[options(free)]
+    series: Vec<String>,
+}
+
+pub fn run(opts: &DbOptions, default_db: &str, program: &str) -> Result<(), Box<dyn Error>> {
+    let command = match &opts.command {
+        Some(c) if c.help_requested() => {
+            println!(
+                "Usage: {} db {} [OPTIONS]\n\n{}",
+                program,
+                c.command_name().unwrap_or_default(),
+                c.self_usage()
+            );
+            return Ok(());
+        }
+        Some(c) => c,
+        None => {
+            println!(
+                "Usage: {} db [OPTIONS] COMMAND\n\n{}\n\nCommands:\n{}",
+                program,
+                DbOptions::usage(),
+                DbCommand::usage()
+            );
+            return Ok(());
+        }
+    };
+    let db = Path::new(opts.db.as_deref().unwrap_or(default_db));
+    match command {
+        DbCommand::Info(_) => info(db),
+        DbCommand::Verify(_) => verify(db),
+        DbCommand::Compact(_) => compact(db),
+        DbCommand::Prune(o) => prune(db, o.older_than),
+        DbCommand::DropSeries(o) => drop_series(db, o),
+    }
+}
+
+/// Takes the lock a recording zenith holds, so the store isn't rewritten under it.
+fn lock(db: &Path) -> Result<Lockfile, Box<dyn Error>> {
+    let lock_path = db.join(".zenith.lock");
+    Lockfile::new(std::process::id(), &lock_path).map_err(|e| match e {
+        LockError::Held(_) => format!(
+            "{} is {}, quit it before changing the database.",
+            lock_path.display(),
+            e
+        )
+        .into(),
+        LockError::Io(e) => format!("{}: {}", lock_path.display(), e).into(),
+    })
+}
+
+fn open(db: &Path) -> Result<HistogramMap, Box<dyn Error>> {
+    open_database(db).map_err(|e| format!("{}: {}", db.display(), e).into())
+}
+
+fn timestamp(t: SystemTime) -> String {
+    DateTime::<Local>::from(t)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn file_size(path: &Path) -> String {
+    let len = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    format!(
+        "{:.1}",
+        Byte::from_u64(len).get_appropriate_unit(UnitType::Binary)
+    )
+}
+
+fn span(d: Duration) -> String {
+    let minutes = d.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (60 * 24), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+fn resolution(tier: &RollupTier) -> String {
+    let secs = tier.resolution.as_secs();
+    if secs.is_multiple_of(3600) {
+        format!("{}h", secs / 3600)
+    } else if secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+fn info(db: &Path) -> Result<(), Box<dyn Error>> {
+    let hm = open(db)?;
+    let store = db.join("store");
+    let configuration = read_configuration(db);
+
+    println!("Database:   {}", db.display());
+    if let Some(version) = configuration.get("version") {
+        match configuration.get("format") {
+            Some(format) => println!("Written by: zenith {}, format {}", version, format),
+            None => println!("Written by: zenith {}", version),
+        }
+    }
+    if let Some(host) = hm.host() {
+        println!("Host:       {}", host);
+    }
+    println!("Tick:       {} ms", hm.tick.as_millis());
+    match (hm.time_at(0), hm.recorded_span()) {
+        (Some(newest), Some(recorded)) => println!(
+            "Recorded:   {} to {} ({})",
+            timestamp(newest.checked_sub(recorded).unwrap_or(newest)),
+            timestamp(newest),
+            span(recorded)
+        ),
+        _ => println!("Recorded:   nothing"),
+    }
+    if let Some(stop) = hm.previous_stop() {
+        println!("Saved:      {}", timestamp(stop));
+    }
+    println!(
+        "Size:       store {}, journal {}",
+        file_size(&store),
+        file_size(&journal_path(&store))
+    );
+
+    let mut kinds: Vec<&HistogramKind> = hm.kinds().collect();
+    kinds.sort_by_key(|k| k.to_string());
+    let width = kinds
+        .iter()
+        .map(|k| k.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("Series".len());
+    print!("\n{:<width$} {:>9}", "Series", "Samples");
+    for tier in hm.tiers() {
+        print!(" {:>9}", format!("{} avg", resolution(tier)));
+    }
+    println!(" {:>10}", "Memory");
+    for kind in kinds {
+        let Some(h) = hm.get(kind) else {
+            continue;
+        };
+        print!("{:<width$} {:>9}", kind.to_string(), h.recorded().count());
+        for t in 0..hm.tiers().len() {
+            print!(" {:>9}", h.recorded_buckets(t));
+        }
+        println!(
+            " {:>10}",
+            format!(
+                "{:.1}",
+                Byte::from_u64(h.size_bytes() as u64).get_appropriate_unit(UnitType::Binary)
+            )
+        );
+    }
+    Ok(())
+}
+
+fn verify(db: &Path) -> Result<(), Box<dyn Error>> {
+    let store = db.join("store");
+    let mut ok = true;
+    if !store.exists() {
+        // everything is in the journal until it is first compacted
+        println!("{}: no store yet", store.display());
+    } else {
+        match read_store(&store) {
+            Ok(hm) => println!("{}: ok, {} series", store.display(), hm.kinds().count()),
+            Err(e) => {
+                println!("{}: {}", store.display(), e);
+                ok = false;
+            }
+        }
+    }
+
+    let journal = journal_path(&store);
+    match check_journal(&journal) {
+        Ok(check) => {
+            if let Some(e) = check.damaged {
+                println!(
+                    "{}: {} segments read back, the rest is skipped: {}",
+                    journal.display(),
+                    check.segments,
+                    e
+                );
+                ok = false;
+            } else if check.torn_bytes > 0 {
+                println!(
+                    "{}: {} segments, {} bytes of an incomplete segment are skipped",
+                    journal.display(),
+                    check.segments,
+                    check.torn_bytes
+                );
+            } else if check.tick.is_some() {
+                println!("{}: ok, {} segments", journal.display(), check.segments);
+            }
+        }
+        Err(e) => {
+            println!("{}: {}", journal.display(), e);
+            ok = false;
+        }
+    }
+
+    for n in 1..=STORE_BACKUPS {
+        let backup = backup_path(&store, n);
+        if !backup.exists() {
+            continue;
+        }
+        match read_store(&backup) {
+            Ok(hm) => match hm.previous_stop() {
+                Some(stop) => println!("{}: ok, saved {}", backup.display(), timestamp(stop)),
+                None => println!("{}: ok", backup.display()),
+            },
+            Err(e) => {
+                println!("{}: {}", backup.display(), e);
+                ok = false;
+            }
+        }
+    }
+
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("{} failed verification", db.display()).into())
+    }
+}
+
+fn compact(db: &Path) -> Result<(), Box<dyn Error>> {
+    let _lock = lock(db)?;
+    let mut hm = open(db)?;
+    let store = db.join("store");
+    let before = file_size(&store);
+    hm.rewrite(db)?;
+    println!(
+        "Compacted {}: {} -> {}",
+        store.display(),
+        before,
+        file_size(&store)
+    );
+    Ok(())
+}
+
+fn prune(db: &Path, older_than: Duration) -> Result<(), Box<dyn Error>> {
+    let _lock = lock(db)?;
+    let mut hm = open(db)?;
+    let cutoff = SystemTime::now()
+        .checked_sub(older_than)
+        .ok_or("--older-than is out of range")?;
+    hm.prune_before(cutoff);
+    hm.rewrite(db)?;
+    println!(
+        "Removed history recorded before {} from {}",
+        timestamp(cutoff),
+        db.display()
+    );
+    Ok(())
+}
+
+/// Disk series whose device is gone. `Total` and names that aren't device paths are kept.
+fn disk_gone(kind: &HistogramKind) -> bool {
+    match kind {
+        HistogramKind::IoRead(d)
+        | HistogramKind::IoWrite(d)
+        | HistogramKind::FileSystemUsedSpace(d) => {
+            let path = Path::new(d);
+            path.is_absolute() && !path.exists()
+        }
+        _ => false,
+    }
+}
+
+/// Network interface series whose interface is gone. Only known on Linux, elsewhere they're kept.
+fn interface_gone(kind: &HistogramKind) -> bool {
+    match kind.interface() {
+        Some(i) if cfg!(target_os = "linux") => !Path::new("/sys/class/net").join(i).exists(),
+        _ => false,
+    }
+}
+
+fn drop_series(db: &Path, opts: &DropSeriesOptions) -> Result<(), Box<dyn Error>> {
+    let _lock = lock(db)?;
+    let mut hm = open(db)?;
+    let mut kinds: Vec<HistogramKind> = if opts.series.is_empty() {
+        hm.kinds()
+            .filter(|k| disk_gone(k) || interface_gone(k))
+            .cloned()
+            .collect()
+    } else {
+        opts.series
+            .iter()
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()?
+    };
+    kinds.sort_by_key(|k| k.to_string());
+    if let Some(k) = kinds.iter().find(|k| hm.get(k).is_none()) {
+        return Err(format!("No recorded series {} in {}", k, db.display()).into());
+    }
+    if kinds.is_empty() {
+        println!("No series to remove.");
+        return Ok(());
+    }
+    for k in kinds.iter() {
+        if opts.dry_run {
+            println!("Would remove {}", k);
+        } else {
+            println!("Removing {}", k);
+            hm.drop_series(k);
+        }
+    }
+    if !opts.dry_run {
+        hm.rewrite(db)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span() {
+        assert_eq!(span(Duration::from_secs(59)), "0m");
+        assert_eq!(span(Duration::from_secs(60 * 90)), "1h 30m");
+        assert_eq!(
+            span(Duration::from_secs(60 * 60 * 24 * 8 + 60 * 60 * 3)),
+            "8d 3h"
+        );
+    }
+
+    #[test]
+    fn test_disk_gone() {
+        assert!(disk_gone(&HistogramKind::IoRead(
+            "/dev/zenith-test-missing".to_string()
+        )));
+        assert!(!disk_gone(&HistogramKind::FileSystemUsedSpace(
+            "Total".to_string()
+        )));
+        let existing = std::env::temp_dir().to_string_lossy().into_owned();
+        assert!(!disk_gone(&HistogramKind::IoWrite(existing)));
+        assert!(!disk_gone(&HistogramKind::Cpu));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_interface_gone() {
+        assert!(interface_gone(&HistogramKind::NetIfaceDropsRx(
+            "zenith-test-missing".to_string()
+        )));
+        assert!(!interface_gone(&HistogramKind::NetIfaceRx(
+            "lo".to_string()
+        )));
+        assert!(!interface_gone(&HistogramKind::NetRx));
+    }
+}
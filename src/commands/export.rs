@@ -0,0 +1,278 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+use crate::metrics::histogram::{open_database, HistogramKind};
+use crate::util::parse_time;
+use chrono::{DateTime, Local, SecondsFormat};
+use gumdrop::Options;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io::{stdout, BufWriter, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+fn parse_format(s: &str) -> Result<ExportFormat, String> {
+    match s {
+        "csv" => Ok(ExportFormat::Csv),
+        "json" => Ok(ExportFormat::Json),
+        "ndjson" => Ok(ExportFormat::Ndjson),
+        _ => Err(format!("{s} Expected one of csv, json or ndjson")),
+    }
+}
+
+#[derive(Options)]
+pub struct ExportOptions {
+    #[options()]
+    help: bool,
+
+    /// Database to export, defaults to the one zenith records to.
+    #[options(no_short, meta = "STRING")]
+    db: Option<String>,
+
+    /// Output format: csv, json or ndjson.
+    #[options(
+        short = "f",
+        default = "csv",
+        parse(try_from_str = "parse_format"),
+        meta = "FORMAT"
+    )]
+    format: ExportFormat,
+
+    /// Comma separated series to export, e.g. cpu,mem,io_read:/dev/sda. Defaults to all.
+    #[options(short = "s", meta = "LIST")]
+    series: Option<String>,
+
+    /// Start of the range, e.g. "2024-05-01 13:30" or -6h.
+    #[options(no_short, meta = "TIME")]
+    from: Option<String>,
+
+    /// End of the range, e.g. "2024-05-01 14:00" or -1h.
+    #[options(no_short, meta = "TIME")]
+    to: Option<String>,
+
+    /// File to write to instead of stdout.
+    #[options(short = "o", meta = "PATH")]
+    output: Option<String>,
+}
+
+/// One timestamp with the value of each exported series, if it has one.
+type Rows = BTreeMap<SystemTime, Vec<Option<u64>>>;
+
+pub fn run(opts: &ExportOptions, default_db: &str) -> Result<(), Box<dyn Error>> {
+    let db = Path::new(opts.db.as_deref().unwrap_or(default_db));
+    let hm = open_database(db).map_err(|e| format!("{}: {}", db.display(), e))?;
+
+    let now = SystemTime::now();
+    let from = opts
+        .from
+        .as_deref()
+        .map(|t| parse_time(t, now))
+        .transpose()?;
+    let to = opts.to.as_deref().map(|t| parse_time(t, now)).transpose()?;
+
+    let series: Vec<HistogramKind> = match &opts.series {
+        Some(s) => s
+            .split(',')
+            .map(|k| k.trim().parse())
+            .collect::<Result<_, _>>()?,
+        None => {
+            let mut all: Vec<HistogramKind> = hm.kinds().cloned().collect();
+            all.sort_by_key(|k| k.to_string());
+            all
+        }
+    };
+
+    let mut rows = Rows::new();
+    for (i, kind) in series.iter().enumerate() {
+        if hm.get(kind).is_none() {
+            return Err(format!("No recorded series {kind} in {}", db.display()).into());
+        }
+        for (t, v) in hm.timestamped(kind) {
+            if from.is_some_and(|f| t < f) || to.is_some_and(|e| t > e) {
+                continue;
+            }
+            rows.entry(t).or_insert_with(|| vec![None; series.len()])[i] = Some(v);
+        }
+    }
+
+    let out: Box<dyn Write> = match &opts.output {
+        Some(path) => Box::new(fs::File::create(path)?),
+        None => Box::new(stdout()),
+    };
+    let mut out = BufWriter::new(out);
+    write_rows(&mut out, opts.format, &series, &rows)?;
+    out.flush()?;
+    Ok(())
+}
+
+fn timestamp(t: &SystemTime) -> String {
+    DateTime::<Local>::from(*t).to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn json_row(series: &[HistogramKind], t: &SystemTime, values: &[Option<u64>]) -> String {
+    let mut row = format!("{{\"timestamp\":{}", json_string(&timestamp(t)));
+    for (k, v) in series.iter().zip(values) {
+        if let Some(v) = v {
+            row.push_str(&format!(",{}:{}", json_string(&k.to_string()), v));
+        }
+    }
+    row.push('}');
+    row
+}
+
+fn write_rows<W: Write>(
+    out: &mut W,
+    format: ExportFormat,
+    series: &[HistogramKind],
+    rows: &Rows,
+) -> std::io::Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            let header: Vec<String> = series.iter().map(|k| csv_field(&k.to_string())).collect();
+            writeln!(out, "timestamp,{}", header.join(","))?;
+            for (t, values) in rows {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|v| v.map(|v| v.to_string()).unwrap_or_default())
+                    .collect();
+                writeln!(out, "{},{}", timestamp(t), values.join(","))?;
+            }
+        }
+        ExportFormat::Json => {
+            writeln!(out, "[")?;
+            for (i, (t, values)) in rows.iter().enumerate() {
+                let sep = if i + 1 < rows.len() { "," } else { "" };
+                writeln!(out, "  {}{}", json_row(series, t, values), sep)?;
+            }
+            writeln!(out, "]")?;
+        }
+        ExportFormat::Ndjson => {
+            for (t, values) in rows {
+                writeln!(out, "{}", json_row(series, t, values))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::histogram::HistogramMap;
+    use std::time::Duration;
+
+    fn rows() -> (Vec<HistogramKind>, Rows) {
+        let series = vec![
+            HistogramKind::Cpu,
+            HistogramKind::IoRead("/dev/sd\"a\"".to_string()),
+        ];
+        let t = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut rows = Rows::new();
+        rows.insert(t, vec![Some(12), None]);
+        rows.insert(t + Duration::from_secs(2), vec![Some(15), Some(4096)]);
+        (series, rows)
+    }
+
+    fn export(format: ExportFormat) -> Vec<String> {
+        let (series, rows) = rows();
+        let mut out = vec![];
+        write_rows(&mut out, format, &series, &rows).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let lines = export(ExportFormat::Csv);
+        assert_eq!(lines[0], "timestamp,cpu,\"io_read:/dev/sd\"\"a\"\"\"");
+        assert!(lines[1].ends_with(",12,"));
+        assert!(lines[2].ends_with(",15,4096"));
+    }
+
+    #[test]
+    fn test_export_ndjson() {
+        let lines = export(ExportFormat::Ndjson);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(",\"cpu\":12}"));
+        assert!(lines[1].ends_with(",\"cpu\":15,\"io_read:/dev/sd\\\"a\\\"\":4096}"));
+    }
+
+    #[test]
+    fn test_export_journal_only() {
+        let db = std::env::temp_dir().join(format!("zenith-test-export-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&db);
+        fs::create_dir_all(&db).unwrap();
+        // a recorder that hasn't compacted yet, nothing but the journal is written
+        let mut hm = HistogramMap::new(
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+            Some(db.clone()),
+        );
+        hm.begin_tick(SystemTime::now());
+        hm.add_value_to(&HistogramKind::Cpu, 37);
+        hm.save_histograms();
+        assert!(!db.join("store").exists());
+
+        let out = db.join("export.csv");
+        let args = [
+            "--db",
+            db.to_str().unwrap(),
+            "-s",
+            "cpu",
+            "-o",
+            out.to_str().unwrap(),
+        ];
+        let opts = ExportOptions::parse_args_default(&args).unwrap();
+        run(&opts, "").unwrap();
+        let csv = fs::read_to_string(&out).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.lines().nth(1).unwrap().ends_with(",37"));
+
+        drop(hm);
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_export_json() {
+        let lines = export(ExportFormat::Json);
+        assert_eq!(lines.first().unwrap(), "[");
+        assert!(lines[1].ends_with("},"));
+        assert!(lines[2].ends_with("}"));
+        assert_eq!(lines.last().unwrap(), "]");
+    }
+}
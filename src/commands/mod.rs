@@ -0,0 +1,8 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Subcommands that work on the recorded history without starting the TUI.
+
+pub mod db;
+pub mod export;
@@ -9,13 +9,18 @@ extern crate log;
 #[cfg(all(target_os = "linux", feature = "nvidia"))]
 extern crate nvml_wrapper as nvml;
 
+mod commands;
 mod constants;
 mod metrics;
 mod renderer;
+mod settings;
 mod util;
 
+use crate::commands::db::DbOptions;
+use crate::commands::export::ExportOptions;
 use crate::renderer::section::{sum_section_heights, Section};
 use crate::renderer::TerminalRenderer;
+use crate::util::parse_duration;
 use gumdrop::Options;
 
 use crossterm::{
@@ -26,17 +31,18 @@ use crossterm::{
     },
 };
 use futures::executor::block_on;
-use metrics::histogram::load_zenith_store;
+use metrics::histogram::{open_database, read_store, Retention, STORE_BACKUPS};
+use metrics::network::NetTotal;
+use metrics::session::{Player, Recorder, Session};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::error::Error;
 use std::fs;
 use std::io::stdout;
 use std::panic;
 use std::panic::PanicHookInfo;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::time::Duration;
-use std::time::SystemTime;
 
 fn panic_hook(info: &PanicHookInfo<'_>) {
     let location = info.location().unwrap(); // The current implementation always returns Some
@@ -72,39 +78,6 @@ fn restore_terminal() {
     disable_raw_mode().expect("Unable to disable raw mode");
 }
 
-fn use_db_history(db_path: &Path, tick_rate: u64) -> Option<bool> {
-    let db_path = db_path.join("store");
-    if db_path.exists() {
-        let now = SystemTime::now();
-        let map = match load_zenith_store(&db_path, &now) {
-            Some(map) => map,
-            None => return Some(true),
-        };
-        let tick = Duration::from_millis(tick_rate);
-        if map.tick != tick {
-            println!(
-                "Using Database:{:?}\nDatabase tick: {} does not match supplied tick: {}.",
-                db_path,
-                map.tick.as_millis(),
-                tick.as_millis()
-            );
-            println!("Proceed with (D)atabase tick: {}, (S)upplied tick with history disabled: {}, (E)xit?:", map.tick.as_millis(), tick.as_millis());
-            let mut line = String::new();
-            let _num_characters = std::io::stdin().read_line(&mut line).unwrap_or_default();
-            match line.as_bytes()[0].to_ascii_lowercase() {
-                b'd' => Some(true),
-                b's' => Some(false),
-                b'e' => None,
-                _ => None,
-            }
-        } else {
-            Some(true)
-        }
-    } else {
-        Some(true)
-    }
-}
-
 macro_rules! push_geometry {
     ($geom:expr, $section:expr, $height:expr) => {
         if $height > 0 {
@@ -171,8 +144,14 @@ fn start_zenith(
     graphics_height: u16,
     disable_history: bool,
     db_path: &str,
+    force_unlock: bool,
+    view: bool,
+    retention: Retention,
+    process_snapshots: usize,
+    net_total: NetTotal,
+    session: Session,
 ) -> Result<(), Box<dyn Error>> {
-    debug!("Starting with Arguments: rate: {}, cpu: {}, net: {}, disk: {}, process: {}, graphics: {}, disable_history: {}, db_path: {}",
+    debug!("Starting with Arguments: rate: {}, cpu: {}, net: {}, disk: {}, process: {}, graphics: {}, disable_history: {}, db_path: {}, force_unlock: {}, view: {}, retention: {:?}, process_snapshots: {}, net_total: {}",
           rate,
           cpu_height,
           net_height,
@@ -181,21 +160,32 @@ fn start_zenith(
           graphics_height,
           disable_history,
           db_path,
+          force_unlock,
+          view,
+          retention,
+          process_snapshots,
+          net_total,
     );
 
     let db_path = Path::new(db_path);
 
-    let use_history = if disable_history {
-        false
-    } else {
-        match use_db_history(db_path, rate) {
-            Some(r) => r,
-            None => {
-                exit(0);
-            }
+    if view {
+        if let Err(e) = open_database(db_path) {
+            println!("Couldn't open {}: {}", db_path.display(), e);
+            exit(1);
         }
+    }
+
+    let replay = matches!(session, Session::Replay(_));
+    // a recording plays at the tick it was made with
+    let rate = match &session {
+        Session::Replay(player) => player.tick().as_millis() as u64,
+        _ => rate,
     };
 
+    // history recorded at another refresh rate is resampled when it's loaded
+    let use_history = !(disable_history || view || replay);
+
     init_terminal();
 
     // setup a panic hook so we can see our panic messages.
@@ -208,7 +198,10 @@ fn start_zenith(
 
     let run = || async {
         //check lock
-        let (db, lock) = if use_history {
+        let (db, lock, lock_error) = if view {
+            // the recorder holds the lock, viewing never writes
+            (Some(db_path.to_owned()), None, None)
+        } else if use_history {
             let db_path = Path::new(db_path);
 
             if !db_path.exists() {
@@ -226,19 +219,25 @@ fn start_zenith(
             debug!("Creating Lock");
 
             let lock_path = db_path.join(".zenith.lock");
-            match util::Lockfile::new(main_pid, &lock_path).await {
-                Some(f) => (Some(db_path.to_owned()), Some(f)), // keeps the lock handle alive
-                None => {
+            let lock = if force_unlock {
+                util::Lockfile::force(main_pid, &lock_path)
+            } else {
+                util::Lockfile::new(main_pid, &lock_path)
+            };
+            match lock {
+                Ok(f) => (Some(db_path.to_owned()), Some(f), None), // keeps the lock handle alive
+                Err(e) => {
+                    let lock_error = format!("{} is {}", lock_path.display(), e);
                     warn!(
-                        "{:} exists and history recording is on. Is another copy of zenith \
-                            open? If not remove the path and open zenith again.",
-                        lock_path.display()
+                        "{} and history recording is on. Is another copy of zenith open? If \
+                            it's gone or hung, start zenith with --force-unlock.",
+                        lock_error
                     );
-                    (None, None)
+                    (None, None, Some(lock_error))
                 }
             }
         } else {
-            (None, None)
+            (None, None, None)
         };
 
         debug!("Create Renderer");
@@ -256,7 +255,18 @@ fn start_zenith(
             Terminal::new(backend).expect("Couldn't create new terminal with backend");
         terminal.hide_cursor().ok();
 
-        let mut r = TerminalRenderer::new(rate, &geometry, db, disable_history);
+        let mut r = TerminalRenderer::new(
+            rate,
+            &geometry,
+            db,
+            disable_history,
+            lock_error,
+            view,
+            retention,
+            process_snapshots,
+            net_total,
+            session,
+        );
 
         r.start(terminal).await;
 
@@ -284,20 +294,107 @@ fn validate_refresh_rate(arg: &str) -> Result<u64, String> {
     }
 }
 
+/// Each host gets its own database so home directories shared between machines, e.g. over
+/// NFS, don't mix their history.
 fn default_db_path() -> String {
     dirs_next::cache_dir()
         .unwrap_or_else(|| Path::new("./").to_owned())
         .join("zenith")
+        .join(util::hostname())
         .to_str()
         .expect("Couldn't set default db path")
         .to_string()
 }
 
+/// Moves the history older zenith kept directly in the cache directory to the database of
+/// this host, unless another host recorded it or a zenith is still recording to it.
+fn adopt_legacy_db(db: &Path) {
+    let Some(legacy) = db.parent() else {
+        return;
+    };
+    if db.exists() || !legacy.join("store").exists() {
+        return;
+    }
+    // held until the history is moved, so an older zenith can't start recording to it meanwhile.
+    // One that crashed left the file behind without the lock.
+    let lock_path = legacy.join(".zenith.lock");
+    let _lock = match util::Lockfile::new(std::process::id(), &lock_path) {
+        Ok(lock) => lock,
+        Err(e) => {
+            info!(
+                "Not moving the history in {}: {} is {}",
+                legacy.display(),
+                lock_path.display(),
+                e
+            );
+            return;
+        }
+    };
+    let host = util::hostname();
+    match read_store(&legacy.join("store")) {
+        Ok(hm) if hm.host().is_some_and(|h| h != host) => {
+            info!(
+                "Not moving the history in {}: it was recorded on {}",
+                legacy.display(),
+                hm.host().unwrap_or_default()
+            );
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            warn!("Not moving {}: {}", legacy.join("store").display(), e);
+            return;
+        }
+    }
+    if let Err(e) = fs::create_dir_all(db) {
+        warn!("Couldn't create {}: {}", db.display(), e);
+        return;
+    }
+    let backups = (1..=STORE_BACKUPS).map(|n| format!("store.{}", n));
+    for name in ["store", "journal", ".configuration"]
+        .iter()
+        .map(|n| n.to_string())
+        .chain(backups)
+    {
+        let from = legacy.join(&name);
+        if from.exists() {
+            if let Err(e) = fs::rename(&from, db.join(&name)) {
+                warn!("Couldn't move {}: {}", from.display(), e);
+            }
+        }
+    }
+    info!(
+        "Moved the history in {} to {}",
+        legacy.display(),
+        db.display()
+    );
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let args = std::env::args().collect::<Vec<_>>();
     let opts =
         ZOptions::parse_args_default(&args[1..]).map_err(|e| format!("{}: {}", args[0], e))?;
 
+    match &opts.command {
+        Some(Command::Export(export)) if export.help_requested() => {
+            println!(
+                "Usage: {} export [OPTIONS]\n\n{}",
+                args[0],
+                ExportOptions::usage()
+            );
+            return Ok(());
+        }
+        Some(Command::Export(export)) => {
+            env_logger::init();
+            return commands::export::run(export, &opts.db);
+        }
+        Some(Command::Db(db)) => {
+            env_logger::init();
+            return commands::db::run(db, &opts.db, &args[0]);
+        }
+        None => {}
+    }
+
     if opts.help_requested() {
         println!(
             "zenith {}
@@ -307,13 +404,17 @@ Up/down arrow keys move around the process table. Return (enter) will focus on a
 Tab switches the active section. Active sections can be expanded (e) and minimized (m).
 Using this you can create the layout you want.
 
-Usage: {} [OPTIONS]
+Usage: {} [OPTIONS] [COMMAND]
 
+{}
+
+Commands:
 {}
 ",
             env!("CARGO_PKG_VERSION"),
             args[0],
-            ZOptions::usage()
+            ZOptions::usage(),
+            ZOptions::command_list().unwrap_or_default()
         );
         return Ok(());
     } else if opts.version {
@@ -336,6 +437,46 @@ Usage: {} [OPTIONS]
     env_logger::init();
     info!("Starting zenith {}", env!("CARGO_PKG_VERSION"));
 
+    let settings = match opts
+        .config
+        .as_deref()
+        .map(PathBuf::from)
+        .or_else(settings::default_path)
+    {
+        Some(path) => settings::load(&path)?,
+        None => settings::Settings::default(),
+    };
+    let defaults = Retention::default();
+    let retention = Retention {
+        memory_window: opts
+            .memory_window
+            .or(settings.memory_window)
+            .unwrap_or(defaults.memory_window),
+        history: opts
+            .history_retention
+            .or(settings.history_retention)
+            .unwrap_or(defaults.history),
+    };
+
+    let session = match (&opts.record, &opts.replay) {
+        (Some(_), Some(_)) => return Err("--record and --replay can't be used together".into()),
+        (_, Some(_)) if opts.view => {
+            return Err("--replay and --view can't be used together".into())
+        }
+        (Some(path), None) => Session::Record(
+            Recorder::create(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?,
+        ),
+        (None, Some(path)) => {
+            Session::Replay(Player::open(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?)
+        }
+        (None, None) => Session::Live,
+    };
+
+    if !opts.view && !opts.disable_history && opts.replay.is_none() && opts.db == default_db_path()
+    {
+        adopt_legacy_db(Path::new(&opts.db));
+    }
+
     start_zenith(
         opts.refresh_rate,
         opts.cpu_height,
@@ -346,6 +487,14 @@ Usage: {} [OPTIONS]
         graphics_height,
         opts.disable_history,
         &opts.db,
+        opts.force_unlock,
+        opts.view,
+        retention,
+        opts.process_snapshots
+            .or(settings.process_snapshots)
+            .unwrap_or(0),
+        opts.net_total.or(settings.net_total).unwrap_or_default(),
+        session,
     )
 }
 
@@ -358,6 +507,10 @@ struct ZOptions {
     #[options()]
     help: bool,
 
+    /// Browse the history another zenith is recording to --db without sampling or saving.
+    #[options(no_short, default = "false")]
+    view: bool,
+
     #[options(short = "V")]
     version: bool,
 
@@ -369,6 +522,50 @@ struct ZOptions {
     #[options(no_short, default_expr = "default_db_path()", meta = "STRING")]
     db: String,
 
+    /// Take the database lock even if another zenith holds it, e.g. one stuck on a hung mount.
+    #[options(no_short, long = "force-unlock", default = "false")]
+    force_unlock: bool,
+
+    /// Settings file. (default: zenith/zenith.conf in the user config directory)
+    #[options(no_short, meta = "PATH")]
+    config: Option<String>,
+
+    /// How long history is kept in the database, e.g. 30d. (default: 365d)
+    #[options(
+        no_short,
+        long = "history-retention",
+        parse(try_from_str = "parse_duration"),
+        meta = "DURATION"
+    )]
+    history_retention: Option<Duration>,
+
+    /// How much history is kept in memory at full resolution, e.g. 1h. (default: 24h)
+    #[options(
+        no_short,
+        long = "memory-window",
+        parse(try_from_str = "parse_duration"),
+        meta = "DURATION"
+    )]
+    memory_window: Option<Duration>,
+
+    /// Snapshots of the process table kept in memory, to show it as it was when scrolling back
+    /// in history. (default: 0, off)
+    #[options(no_short, long = "process-snapshots", meta = "COUNT")]
+    process_snapshots: Option<usize>,
+
+    /// Interfaces counted in the network total: physical, all or a list like eth0,wlan0.
+    /// (default: physical)
+    #[options(no_short, long = "net-total", meta = "INTERFACES")]
+    net_total: Option<NetTotal>,
+
+    /// Record everything zenith samples to a file, to look at it later with --replay.
+    #[options(no_short, meta = "PATH")]
+    record: Option<String>,
+
+    /// Play a file written with --record instead of sampling this machine.
+    #[options(no_short, meta = "PATH")]
+    replay: Option<String>,
+
     /// Min Percent Height of Disk visualization.
     #[options(short = "d", long = "disk-height", default = "17", meta = "INT")]
     disk_height: u16,
@@ -395,4 +592,15 @@ struct ZOptions {
     #[cfg(feature = "nvidia")]
     #[options(short = "g", long = "graphics-height", default = "17", meta = "INT")]
     graphics_height: u16,
+
+    #[options(command)]
+    command: Option<Command>,
+}
+
+#[derive(Options)]
+enum Command {
+    /// Write recorded history as CSV, JSON or NDJSON.
+    Export(ExportOptions),
+    /// Inspect and maintain the history database: info, verify, compact, prune, drop-series.
+    Db(DbOptions),
 }
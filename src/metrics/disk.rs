@@ -6,6 +6,7 @@ use crate::util::percent_of;
 use futures::StreamExt;
 use heim::disk::{io_counters, IoCounters};
 use heim::units::information::byte;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs::{canonicalize, read_link};
@@ -14,7 +15,7 @@ use std::path::PathBuf;
 use std::time::Duration;
 use sysinfo::Disk;
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct IoMetrics {
     pub read_bytes: u64,
     pub write_bytes: u64,
@@ -48,7 +49,7 @@ impl ops::AddAssign<IoMetrics> for IoMetrics {
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct ZDisk {
     pub mount_point: PathBuf,
     pub available_bytes: u64,
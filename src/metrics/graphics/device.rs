@@ -2,6 +2,8 @@
  * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
  */
 
+use serde_derive::{Deserialize, Serialize};
+
 pub trait GraphicsExt {
     fn update_gfx_devices(&mut self);
     #[allow(dead_code)]
@@ -10,7 +12,7 @@ pub trait GraphicsExt {
 }
 
 #[allow(dead_code)]
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GraphicsDeviceProcess {
     pub pid: i32,
     pub timestamp: u64,
@@ -20,7 +22,7 @@ pub struct GraphicsDeviceProcess {
     pub dec_utilization: u32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct GraphicsDevice {
     pub name: String,
     pub gpu_utilization: u32,
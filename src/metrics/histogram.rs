@@ -2,39 +2,435 @@
  * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
  */
 
+use crate::metrics::journal;
+use crate::metrics::samples::Samples;
+use crate::metrics::top::{Consumer, TopHistory};
 use crate::restore_terminal;
+use crate::util::hostname;
+use chrono::{DateTime, Local};
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use serde_derive::{Deserialize, Serialize};
-use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
 use std::fs;
 use std::io::prelude::*;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
-const ONE_WEEK: u64 = 60 * 60 * 24 * 7;
-const DB_ERROR: &str = "Couldn't open database.";
 const DSER_ERROR: &str = "Couldn't deserialize object";
 const SER_ERROR: &str = "Couldn't serialize object";
+/// Number of journal segments appended before the store is rewritten.
+const JOURNAL_COMPACT_SEGMENTS: usize = 30;
+const STORE_MAGIC: &[u8; 4] = b"ZNTH";
+const STORE_HEADER_LEN: usize = 8;
+/// On-disk format of the store and journal.
+/// 1: headerless store written by zenith 0.15 and earlier.
+/// 2: magic/version header and crc32 of the compressed store, rollup tiers, the journal,
+///    capture time of every raw sample, delta encoded samples and rollup buckets, host the
+///    history was recorded on, top consumers per tick, network interface and TCP series.
+pub const STORE_VERSION: u32 = 2;
+/// Number of previous stores kept as `store.1` (newest) to `store.N`.
+pub const STORE_BACKUPS: usize = 3;
+
+/// Marks a tick with no sample, e.g. while zenith wasn't running.
+pub const NO_DATA: u64 = u64::MAX;
+
+/// Downsampled tiers kept next to the raw samples, finest first.
+pub const ROLLUP_TIERS: &[RollupTier] = &[
+    RollupTier {
+        resolution: Duration::from_secs(60),
+        retention: Duration::from_secs(60 * 60 * 24 * 30),
+    },
+    RollupTier {
+        resolution: Duration::from_secs(60 * 60),
+        retention: Duration::from_secs(60 * 60 * 24 * 365),
+    },
+];
+
+/// How long samples are kept in memory at full resolution and on disk at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub memory_window: Duration,
+    pub history: Duration,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            memory_window: Duration::from_secs(60 * 60 * 24),
+            history: Duration::from_secs(60 * 60 * 24 * 365),
+        }
+    }
+}
+
+impl Retention {
+    /// Rollup tiers clamped to the history retention, the coarsest one keeps all of it.
+    fn tiers(&self) -> Vec<RollupTier> {
+        let last = ROLLUP_TIERS.len() - 1;
+        ROLLUP_TIERS
+            .iter()
+            .enumerate()
+            .map(|(i, t)| RollupTier {
+                resolution: t.resolution,
+                retention: if i == last {
+                    self.history
+                } else {
+                    t.retention.min(self.history)
+                },
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollupTier {
+    pub resolution: Duration,
+    pub retention: Duration,
+}
+
+impl RollupTier {
+    /// Number of ticks that are folded into one bucket of this tier.
+    pub fn ticks_per_bucket(&self, tick: Duration) -> u64 {
+        (self.resolution.as_millis() / tick.as_millis().max(1)).max(1) as u64
+    }
+
+    fn max_buckets(&self) -> usize {
+        (self.retention.as_secs() / self.resolution.as_secs().max(1)) as usize
+    }
+}
+
+/// Min, max and sum of the samples that fell into one rollup bucket.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Bucket {
+    pub min: u64,
+    pub max: u64,
+    pub sum: u64,
+    pub count: u64,
+}
+
+impl Bucket {
+    fn add(&mut self, val: u64, n: u64) {
+        if n == 0 || val == NO_DATA {
+            return;
+        }
+        if self.count == 0 {
+            self.min = val;
+            self.max = val;
+        } else {
+            self.min = self.min.min(val);
+            self.max = self.max.max(val);
+        }
+        self.sum = self.sum.saturating_add(val.saturating_mul(n));
+        self.count += n;
+    }
+
+    fn merge(&mut self, other: &Bucket) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+        } else {
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+            self.sum = self.sum.saturating_add(other.sum);
+            self.count += other.count;
+        }
+    }
+
+    /// Mean of the samples, `NO_DATA` if the bucket only covers gaps.
+    pub fn avg(&self) -> u64 {
+        self.sum.checked_div(self.count).unwrap_or(NO_DATA)
+    }
+
+    /// The bucket of totals over ticks of `from` as if they had been measured over ticks of
+    /// `to`, see `per_tick_scale`.
+    fn scaled(&self, from: Duration, to: Duration) -> Bucket {
+        if self.count == 0 {
+            return *self;
+        }
+        Bucket {
+            min: per_tick_scale(self.min, from, to),
+            max: per_tick_scale(self.max, from, to),
+            sum: per_tick_scale(self.sum, from, to),
+            count: self.count,
+        }
+    }
+}
+
+/// A total over a tick of `from` scaled to a tick of `to`, at the same rate.
+fn per_tick_scale(v: u64, from: Duration, to: Duration) -> u64 {
+    if v == NO_DATA {
+        return v;
+    }
+    let (from_ms, to_ms) = (from.as_millis().max(1), to.as_millis().max(1));
+    (v as u128 * to_ms / from_ms).min(NO_DATA as u128 - 1) as u64
+}
+
+/// Closed buckets of a rollup tier, one compressed column per field.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Buckets {
+    min: Samples,
+    max: Samples,
+    sum: Samples,
+    count: Samples,
+}
+
+impl Buckets {
+    fn push(&mut self, b: Bucket, n: usize) {
+        self.min.push(b.min, n);
+        self.max.push(b.max, n);
+        self.sum.push(b.sum, n);
+        self.count.push(b.count, n);
+    }
+
+    fn len(&self) -> usize {
+        self.count.len()
+    }
+
+    fn truncate_front(&mut self, keep: usize) {
+        self.min.truncate_front(keep);
+        self.max.truncate_front(keep);
+        self.sum.truncate_front(keep);
+        self.count.truncate_front(keep);
+    }
+
+    fn range(&self, start: usize, end: usize) -> Vec<Bucket> {
+        let min = self.min.range(start, end);
+        let max = self.max.range(start, end);
+        let sum = self.sum.range(start, end);
+        let count = self.count.range(start, end);
+        (0..count.len())
+            .map(|i| Bucket {
+                min: min[i],
+                max: max[i],
+                sum: sum[i],
+                count: count[i],
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Rollup {
+    buckets: Buckets,
+    pending: Bucket,
+    pending_ticks: u64,
+}
+
+impl Rollup {
+    /// Folds `n` ticks of `val` into the tier, closing buckets as they fill up.
+    fn push(&mut self, val: u64, mut n: u64, ticks_per_bucket: u64, max_buckets: usize) {
+        while n > 0 {
+            if self.pending_ticks == 0 && n >= ticks_per_bucket {
+                // whole buckets of the same value, no need to go tick by tick
+                let whole = (n / ticks_per_bucket).min(max_buckets as u64 + 1);
+                let mut b = Bucket::default();
+                b.add(val, ticks_per_bucket);
+                self.buckets.push(b, whole as usize);
+                n -= (n / ticks_per_bucket) * ticks_per_bucket;
+                continue;
+            }
+            let take = n.min(ticks_per_bucket - self.pending_ticks);
+            self.pending.add(val, take);
+            self.pending_ticks += take;
+            n -= take;
+            if self.pending_ticks >= ticks_per_bucket {
+                self.buckets.push(self.pending, 1);
+                self.pending = Bucket::default();
+                self.pending_ticks = 0;
+            }
+        }
+        self.prune(max_buckets);
+    }
+
+    /// Keeps the partly filled bucket covering the same time when the tick changes. `per_tick`
+    /// totals are scaled to the new tick like the raw samples.
+    fn retick(&mut self, from: Duration, to: Duration, per_tick: bool) {
+        let ticks = self.pending_ticks as u128 * from.as_millis() / to.as_millis().max(1);
+        self.pending_ticks = ticks as u64;
+        if per_tick {
+            let mut buckets = Buckets::default();
+            for b in self.buckets.range(0, self.buckets.len()) {
+                buckets.push(b.scaled(from, to), 1);
+            }
+            self.buckets = buckets;
+            self.pending = self.pending.scaled(from, to);
+        }
+    }
+
+    fn prune(&mut self, max_buckets: usize) {
+        self.buckets.truncate_front(max_buckets);
+    }
+
+    /// Oldest bucket that has samples, counted back from the newest one.
+    fn recorded_len(&self) -> usize {
+        let closed = self.buckets.len();
+        let first = self
+            .buckets
+            .count
+            .position(|c| c > 0)
+            .or_else(|| (self.pending_ticks > 0 && self.pending.count > 0).then_some(closed));
+        first.map_or(0, |f| self.len() - f)
+    }
+
+    /// Buckets `start..end`, the one still being filled follows the closed ones.
+    fn range(&self, start: usize, end: usize) -> Vec<Bucket> {
+        let closed = self.buckets.len();
+        let mut buckets = self.buckets.range(start, end);
+        if self.pending_ticks > 0 && start <= closed && end > closed {
+            buckets.push(self.pending);
+        }
+        buckets
+    }
+
+    fn len(&self) -> usize {
+        self.buckets.len() + usize::from(self.pending_ticks > 0)
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Histogram<'a> {
-    data: Cow<'a, [u64]>,
+pub struct Histogram {
+    data: Samples,
+    rollups: Vec<Rollup>,
 }
 
-impl Histogram<'_> {
-    fn new(size: usize) -> Self {
+impl Histogram {
+    fn new(size: usize, tiers: usize) -> Self {
+        let mut data = Samples::default();
+        data.push(NO_DATA, size);
+        Histogram {
+            data,
+            rollups: vec![Rollup::default(); tiers],
+        }
+    }
+
+    fn view(data: Vec<u64>) -> Histogram {
         Histogram {
-            data: vec![0; size].into(),
+            data: data.into(),
+            rollups: vec![],
+        }
+    }
+
+    pub fn data(&self) -> Vec<u64> {
+        self.data.to_vec()
+    }
+
+    /// Samples with gaps as `None`.
+    pub fn points(&self) -> impl Iterator<Item = Option<u64>> + '_ {
+        self.data.iter().map(|v| (v != NO_DATA).then_some(v))
+    }
+
+    /// Samples that were actually recorded, skipping gaps.
+    pub fn recorded(&self) -> impl Iterator<Item = u64> + '_ {
+        self.data.iter().filter(|v| *v != NO_DATA)
+    }
+
+    /// Buckets of rollup tier `tier` back to the oldest one with samples.
+    pub fn recorded_buckets(&self, tier: usize) -> usize {
+        self.rollups.get(tier).map_or(0, Rollup::recorded_len)
+    }
+
+    /// Statistics of the recorded samples, `None` if there are only gaps. Percentiles are the
+    /// nearest recorded sample.
+    pub fn stats(&self) -> Option<Stats> {
+        let mut sorted: Vec<u64> = self.recorded().collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_unstable();
+        Some(Stats {
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: sorted.iter().map(|v| *v as f64).sum::<f64>() / sorted.len() as f64,
+            p50: percentile(&sorted, 50),
+            p95: percentile(&sorted, 95),
+            p99: percentile(&sorted, 99),
+        })
+    }
+
+    /// Newest sample, `None` if it is a gap.
+    pub fn last(&self) -> Option<u64> {
+        self.data.last().filter(|v| *v != NO_DATA)
+    }
+
+    /// Raw samples since the newest one that isn't a gap, all of them if there is none.
+    fn gaps_since_sample(&self) -> usize {
+        let len = self.data.len();
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| *v != NO_DATA)
+            .last()
+            .map_or(len, |(i, _)| len - 1 - i)
+    }
+
+    /// Memory used by the raw samples and rollup tiers.
+    pub fn size_bytes(&self) -> usize {
+        self.data.size_bytes()
+            + self
+                .rollups
+                .iter()
+                .map(|r| {
+                    let b = &r.buckets;
+                    b.min.size_bytes()
+                        + b.max.size_bytes()
+                        + b.sum.size_bytes()
+                        + b.count.size_bytes()
+                })
+                .sum::<usize>()
+    }
+
+    /// Converts the raw samples from ticks of `from` to ticks of `to`, keeping the newest
+    /// sample at the same time. Samples are averaged when the tick grows and repeated when it
+    /// shrinks. `per_tick` values are totals over one tick and are scaled to the new length.
+    fn resample(&mut self, from: Duration, to: Duration, raw_max: usize, per_tick: bool) {
+        let (from_ms, to_ms) = (from.as_millis().max(1), to.as_millis().max(1));
+        let old = self.data();
+        let len = ((old.len() as u128 * from_ms).div_ceil(to_ms) as usize).min(raw_max);
+        let scale = |v: u64| {
+            if per_tick {
+                per_tick_scale(v, from, to)
+            } else {
+                v
+            }
+        };
+        // walk back from the newest sample, k and m count ticks of the new and the old length
+        let mut data: Vec<u64> = (0..len as u128)
+            .map(|k| {
+                // old samples overlapping the new tick
+                let first = (k * to_ms / from_ms) as usize;
+                let last = ((k + 1) * to_ms).div_ceil(from_ms) as usize;
+                let mut b = Bucket::default();
+                for m in first..last.min(old.len()) {
+                    b.add(scale(old[old.len() - 1 - m]), 1);
+                }
+                b.avg()
+            })
+            .collect();
+        data.reverse();
+        self.data = data.into();
+        for rollup in self.rollups.iter_mut() {
+            rollup.retick(from, to, per_tick);
         }
     }
 
-    pub fn data(&self) -> &[u64] {
-        self.data.as_ref()
+    /// Appends `n` ticks of `val` to the raw samples and every rollup tier.
+    fn push(&mut self, val: u64, n: u64, raw_max: usize, tiers: &[RollupTier], tick: Duration) {
+        self.data.push(val, n.min(raw_max as u64 + 1) as usize);
+        // trim in batches so the oldest chunks are dropped whole rather than one sample at a time
+        if self.data.len() > raw_max + raw_max / 16 {
+            self.data.truncate_front(raw_max);
+        }
+        for (tier, rollup) in tiers.iter().zip(self.rollups.iter_mut()) {
+            rollup.push(val, n, tier.ticks_per_bucket(tick), tier.max_buckets());
+        }
     }
 }
 
@@ -49,22 +445,243 @@ pub enum HistogramKind {
     GpuUse(String),
     GpuMem(String),
     FileSystemUsedSpace(String),
+    NetIfaceTx(String),
+    NetIfaceRx(String),
+    NetIfacePacketsTx(String),
+    NetIfacePacketsRx(String),
+    NetIfaceErrorsTx(String),
+    NetIfaceErrorsRx(String),
+    NetIfaceDropsTx(String),
+    NetIfaceDropsRx(String),
+    TcpRetransmits,
+    TcpResets,
+    TcpListenOverflows,
+}
+
+impl HistogramKind {
+    /// Values are totals over one tick rather than rates or levels.
+    fn per_tick(&self) -> bool {
+        matches!(
+            self,
+            HistogramKind::NetTx
+                | HistogramKind::NetRx
+                | HistogramKind::NetIfaceTx(_)
+                | HistogramKind::NetIfaceRx(_)
+                | HistogramKind::NetIfacePacketsTx(_)
+                | HistogramKind::NetIfacePacketsRx(_)
+                | HistogramKind::NetIfaceErrorsTx(_)
+                | HistogramKind::NetIfaceErrorsRx(_)
+                | HistogramKind::NetIfaceDropsTx(_)
+                | HistogramKind::NetIfaceDropsRx(_)
+                | HistogramKind::TcpRetransmits
+                | HistogramKind::TcpResets
+                | HistogramKind::TcpListenOverflows
+        )
+    }
+
+    /// All series recorded for the network interface `name`.
+    pub fn of_interface(name: &str) -> [HistogramKind; 8] {
+        let name = name.to_string();
+        [
+            HistogramKind::NetIfaceTx(name.clone()),
+            HistogramKind::NetIfaceRx(name.clone()),
+            HistogramKind::NetIfacePacketsTx(name.clone()),
+            HistogramKind::NetIfacePacketsRx(name.clone()),
+            HistogramKind::NetIfaceErrorsTx(name.clone()),
+            HistogramKind::NetIfaceErrorsRx(name.clone()),
+            HistogramKind::NetIfaceDropsTx(name.clone()),
+            HistogramKind::NetIfaceDropsRx(name),
+        ]
+    }
+
+    /// Name of the network interface of a per interface series.
+    pub fn interface(&self) -> Option<&str> {
+        match self {
+            HistogramKind::NetIfaceTx(i)
+            | HistogramKind::NetIfaceRx(i)
+            | HistogramKind::NetIfacePacketsTx(i)
+            | HistogramKind::NetIfacePacketsRx(i)
+            | HistogramKind::NetIfaceErrorsTx(i)
+            | HistogramKind::NetIfaceErrorsRx(i)
+            | HistogramKind::NetIfaceDropsTx(i)
+            | HistogramKind::NetIfaceDropsRx(i) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HistogramKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HistogramKind::Cpu => write!(f, "cpu"),
+            HistogramKind::Mem => write!(f, "mem"),
+            HistogramKind::NetTx => write!(f, "net_tx"),
+            HistogramKind::NetRx => write!(f, "net_rx"),
+            HistogramKind::IoRead(d) => write!(f, "io_read:{}", d),
+            HistogramKind::IoWrite(d) => write!(f, "io_write:{}", d),
+            HistogramKind::GpuUse(g) => write!(f, "gpu_use:{}", g),
+            HistogramKind::GpuMem(g) => write!(f, "gpu_mem:{}", g),
+            HistogramKind::FileSystemUsedSpace(m) => write!(f, "fs_used:{}", m),
+            HistogramKind::NetIfaceTx(i) => write!(f, "net_tx:{}", i),
+            HistogramKind::NetIfaceRx(i) => write!(f, "net_rx:{}", i),
+            HistogramKind::NetIfacePacketsTx(i) => write!(f, "net_tx_packets:{}", i),
+            HistogramKind::NetIfacePacketsRx(i) => write!(f, "net_rx_packets:{}", i),
+            HistogramKind::NetIfaceErrorsTx(i) => write!(f, "net_tx_errors:{}", i),
+            HistogramKind::NetIfaceErrorsRx(i) => write!(f, "net_rx_errors:{}", i),
+            HistogramKind::NetIfaceDropsTx(i) => write!(f, "net_tx_drops:{}", i),
+            HistogramKind::NetIfaceDropsRx(i) => write!(f, "net_rx_drops:{}", i),
+            HistogramKind::TcpRetransmits => write!(f, "tcp_retransmits"),
+            HistogramKind::TcpResets => write!(f, "tcp_resets"),
+            HistogramKind::TcpListenOverflows => write!(f, "tcp_listen_overflows"),
+        }
+    }
+}
+
+impl FromStr for HistogramKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg.to_string())),
+            None => (s, None),
+        };
+        match (name, arg) {
+            ("cpu", None) => Ok(HistogramKind::Cpu),
+            ("mem", None) => Ok(HistogramKind::Mem),
+            ("net_tx", None) => Ok(HistogramKind::NetTx),
+            ("net_rx", None) => Ok(HistogramKind::NetRx),
+            ("io_read", Some(d)) => Ok(HistogramKind::IoRead(d)),
+            ("io_write", Some(d)) => Ok(HistogramKind::IoWrite(d)),
+            ("gpu_use", Some(g)) => Ok(HistogramKind::GpuUse(g)),
+            ("gpu_mem", Some(g)) => Ok(HistogramKind::GpuMem(g)),
+            ("fs_used", Some(m)) => Ok(HistogramKind::FileSystemUsedSpace(m)),
+            ("net_tx", Some(i)) => Ok(HistogramKind::NetIfaceTx(i)),
+            ("net_rx", Some(i)) => Ok(HistogramKind::NetIfaceRx(i)),
+            ("net_tx_packets", Some(i)) => Ok(HistogramKind::NetIfacePacketsTx(i)),
+            ("net_rx_packets", Some(i)) => Ok(HistogramKind::NetIfacePacketsRx(i)),
+            ("net_tx_errors", Some(i)) => Ok(HistogramKind::NetIfaceErrorsTx(i)),
+            ("net_rx_errors", Some(i)) => Ok(HistogramKind::NetIfaceErrorsRx(i)),
+            ("net_tx_drops", Some(i)) => Ok(HistogramKind::NetIfaceDropsTx(i)),
+            ("net_rx_drops", Some(i)) => Ok(HistogramKind::NetIfaceDropsRx(i)),
+            ("tcp_retransmits", None) => Ok(HistogramKind::TcpRetransmits),
+            ("tcp_resets", None) => Ok(HistogramKind::TcpResets),
+            ("tcp_listen_overflows", None) => Ok(HistogramKind::TcpListenOverflows),
+            _ => Err(format!("unknown series: {}", s)),
+        }
+    }
 }
+
 #[derive(Clone, Copy)]
 pub struct View {
     pub zoom_factor: u32,
     pub update_number: u32,
     pub width: usize,
     pub offset: usize,
+    pub cursor: Option<Cursor>,
+}
+
+/// A column picked on the charts, counted from the newest one shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cursor {
+    pub column: usize,
+    /// Column where a range being selected starts.
+    pub mark: Option<usize>,
+}
+
+/// Summary of the recorded samples of a histogram.
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+impl Stats {
+    /// Statistics of rollup buckets: the lowest minimum, highest maximum and mean of all their
+    /// samples, with the percentiles of the means of the buckets. None if they only cover gaps.
+    fn of_buckets(buckets: &[Bucket]) -> Option<Stats> {
+        let mut total = Bucket::default();
+        buckets.iter().for_each(|b| total.merge(b));
+        let mut means: Vec<u64> = buckets
+            .iter()
+            .filter(|b| b.count > 0)
+            .map(Bucket::avg)
+            .collect();
+        if means.is_empty() {
+            return None;
+        }
+        means.sort_unstable();
+        Some(Stats {
+            min: total.min,
+            max: total.max,
+            mean: total.sum as f64 / total.count as f64,
+            p50: percentile(&means, 50),
+            p95: percentile(&means, 95),
+            p99: percentile(&means, 99),
+        })
+    }
+}
+
+/// Nearest value at `p` percent of the non-empty `sorted`.
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    sorted[(sorted.len() * p).div_ceil(100).max(1) - 1]
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HistogramMap {
-    map: HashMap<HistogramKind, Histogram<'static>>,
+    map: HashMap<HistogramKind, Histogram>,
     duration: Duration,
     pub tick: Duration,
     db: Option<PathBuf>,
     previous_stop: Option<SystemTime>,
+    tiers: Vec<RollupTier>,
+    /// When each of the newest raw samples was captured in milliseconds since the epoch,
+    /// oldest first.
+    times: Samples,
+    /// Host that records to the store, stores of other hosts are never written to.
+    host: Option<String>,
+    /// Processes using the most of each resource, per tick of the newest raw samples.
+    top: TopHistory,
+    /// Samples added since the last save, written out as the next journal segment.
+    #[serde(skip)]
+    unsaved: HashMap<HistogramKind, Vec<u64>>,
+    #[serde(skip)]
+    unsaved_times: Vec<SystemTime>,
+    #[serde(skip)]
+    unsaved_top: Vec<Vec<Consumer>>,
+    #[serde(skip)]
+    journal_segments: usize,
+    /// Format version of a store left untouched because a newer zenith wrote it.
+    #[serde(skip)]
+    newer_store: Option<u32>,
+    /// Host of a store left untouched because another host records to it.
+    #[serde(skip)]
+    other_host: Option<String>,
+    /// Why a store left untouched couldn't be read, e.g. for its permissions.
+    #[serde(skip)]
+    unreadable_store: Option<String>,
+    /// Network interfaces that went away, with the raw samples their series missed since.
+    #[serde(skip)]
+    gone_interfaces: HashMap<String, usize>,
+    /// Database recorded by another instance that is shown instead of new samples.
+    #[serde(skip)]
+    viewing: Option<PathBuf>,
+    /// Modification time of the viewed store and journal when they were last read.
+    #[serde(skip)]
+    viewed_at: Option<SystemTime>,
+}
+
+/// Samples recorded between two saves, appended to the journal next to the store.
+#[derive(Serialize, Deserialize, Debug)]
+struct Segment {
+    stop: SystemTime,
+    samples: Vec<(HistogramKind, Vec<u64>)>,
+    times: Vec<SystemTime>,
+    /// Top consumers of each of `times`.
+    top: Vec<Vec<Consumer>>,
 }
 
 macro_rules! exit_with_message {
@@ -75,106 +692,795 @@ macro_rules! exit_with_message {
     };
 }
 
-pub fn load_zenith_store(path: &Path, current_time: &SystemTime) -> Option<HistogramMap> {
-    // need to fill in time between when it was last stored and now, like the sled DB
-    let mut data = std::fs::read(path).expect(DB_ERROR);
+#[derive(Debug)]
+pub enum StoreError {
+    Io(std::io::Error),
+    Corrupt(String),
+    /// Written by a newer zenith, must be left untouched.
+    TooNew(u32),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "{}", e),
+            StoreError::Corrupt(e) => write!(f, "{}: {}", DSER_ERROR, e),
+            StoreError::TooNew(v) => write!(
+                f,
+                "store format {} was written by a newer zenith, this version supports up to {}",
+                v, STORE_VERSION
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for StoreError {
+    fn from(e: bincode::Error) -> Self {
+        StoreError::Corrupt(e.to_string())
+    }
+}
+
+fn format_header() -> Vec<u8> {
+    let mut header = STORE_MAGIC.to_vec();
+    header.extend_from_slice(&STORE_VERSION.to_le_bytes());
+    header
+}
+
+/// First frame of a journal, the format header followed by the tick of its segments.
+fn journal_header(tick: Duration) -> Vec<u8> {
+    let mut header = format_header();
+    header.extend(bincode::serialize(&tick).expect(SER_ERROR));
+    header
+}
+
+/// Format version from the header at the start of `data`, if it has one.
+fn parse_format_header(data: &[u8]) -> Option<u32> {
+    if data.len() < STORE_HEADER_LEN || &data[..4] != STORE_MAGIC {
+        return None;
+    }
+    let mut version = [0; 4];
+    version.copy_from_slice(&data[4..STORE_HEADER_LEN]);
+    Some(u32::from_le_bytes(version))
+}
+
+fn decompress(data: &[u8]) -> Vec<u8> {
     debug!("Attempting to decompress database...");
-    let mut gz = GzDecoder::new(&data[..]);
+    let mut gz = GzDecoder::new(data);
     if gz.header().is_some() {
         let mut udata = Vec::new();
         debug!("Decompressing...");
-        let result = gz.read_to_end(&mut udata);
-        if result.is_ok() {
-            data = udata;
+        if gz.read_to_end(&mut udata).is_ok() {
             debug!("Decompressed");
+            return udata;
         }
     } else {
         debug!("Not a gzip file.");
     }
-    let mut hm: HistogramMap = match bincode::deserialize(&data) {
-        Ok(hm) => hm,
-        Err(e) => {
-            error!("{}: {}", DSER_ERROR, e,);
-            return None;
+    data.to_vec()
+}
+
+/// Layout of the headerless store of zenith 0.15 and earlier, only used to migrate it.
+mod legacy {
+    use super::HistogramKind;
+    use serde_derive::Deserialize;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    #[derive(Deserialize)]
+    pub struct HistogramV1 {
+        pub data: Vec<u64>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct HistogramMapV1 {
+        pub map: HashMap<HistogramKind, HistogramV1>,
+        pub duration: Duration,
+        pub tick: Duration,
+        pub db: Option<PathBuf>,
+        pub previous_stop: Option<SystemTime>,
+    }
+}
+
+/// Builds the rollup tiers from the raw samples of a version 1 store.
+fn migrate_v1(data: &[u8]) -> Result<HistogramMap, StoreError> {
+    let old: legacy::HistogramMapV1 = bincode::deserialize(data)?;
+    let mut hm = HistogramMap::empty(old.duration, old.tick, old.db);
+    hm.previous_stop = old.previous_stop;
+    let raw_max = hm.raw_max();
+    for (k, v) in old.map {
+        let mut h = Histogram::new(0, hm.tiers.len());
+        for val in v.data {
+            h.push(val, 1, raw_max, &hm.tiers, hm.tick);
+        }
+        hm.map.insert(k, h);
+    }
+    Ok(hm)
+}
+
+fn decode_store(data: &[u8]) -> Result<HistogramMap, StoreError> {
+    match parse_format_header(data) {
+        Some(version) if version > STORE_VERSION => Err(StoreError::TooNew(version)),
+        Some(STORE_VERSION) => {
+            let payload = verify_checksum(&data[STORE_HEADER_LEN..])?;
+            Ok(bincode::deserialize(&decompress(payload))?)
+        }
+        Some(version) => Err(StoreError::Corrupt(format!(
+            "unknown store format {}",
+            version
+        ))),
+        None => {
+            info!("Migrating store from format 1 to {}", STORE_VERSION);
+            migrate_v1(&decompress(data))
+        }
+    }
+}
+
+/// Strips the checksum in front of the compressed store, failing if it doesn't match.
+fn verify_checksum(data: &[u8]) -> Result<&[u8], StoreError> {
+    if data.len() < 4 {
+        return Err(StoreError::Corrupt("store is truncated".to_string()));
+    }
+    let (crc, payload) = data.split_at(4);
+    let crc = u32::from_le_bytes(crc.try_into().expect("4 bytes"));
+    if journal::checksum(payload) != crc {
+        return Err(StoreError::Corrupt("store checksum mismatch".to_string()));
+    }
+    Ok(payload)
+}
+
+/// Reads back the `key=value` pairs of the `.configuration` file in the db directory.
+pub fn read_configuration(db: &Path) -> HashMap<String, String> {
+    fs::read_to_string(db.join(".configuration"))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| l.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
+
+fn epoch_millis(t: SystemTime) -> u64 {
+    t.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+fn from_epoch_millis(ms: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis(ms)
+}
+
+pub fn journal_path(store: &Path) -> PathBuf {
+    store.with_file_name("journal")
+}
+
+pub fn backup_path(store: &Path, n: usize) -> PathBuf {
+    store.with_file_name(format!("store.{}", n))
+}
+
+/// Shifts `store.1..N` up by one, dropping the oldest, and links the current store as
+/// `store.1` so a store is in place even if the new one is never renamed over it.
+fn rotate_backups(store: &Path) -> std::io::Result<()> {
+    if !store.exists() {
+        return Ok(());
+    }
+    for n in (1..STORE_BACKUPS).rev() {
+        let from = backup_path(store, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(store, n + 1))?;
+        }
+    }
+    let newest = backup_path(store, 1);
+    if newest.exists() {
+        fs::remove_file(&newest)?;
+    }
+    if fs::hard_link(store, &newest).is_err() {
+        fs::copy(store, &newest)?;
+    }
+    Ok(())
+}
+
+/// Moves a store that can't be read out of the way, keeping it for inspection.
+fn set_aside(store: &Path) {
+    let stamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let aside = store.with_file_name(format!(
+        "{}.corrupt-{}",
+        store.file_name().unwrap_or_default().to_string_lossy(),
+        stamp
+    ));
+    match fs::rename(store, &aside) {
+        Ok(()) => warn!(
+            "Moved unreadable {} to {}",
+            store.display(),
+            aside.display()
+        ),
+        Err(e) => error!("Couldn't move {} aside: {}", store.display(), e),
+    }
+}
+
+/// Loads the store, falling back to the newest backup that reads back intact when it is
+/// missing or damaged. Damaged files are set aside, never deleted. A store that can't be read
+/// at all, e.g. for its permissions, may well be intact and is only reported.
+fn load_with_backups(store: &Path, current_time: &SystemTime) -> Result<HistogramMap, StoreError> {
+    let err = if store.exists() {
+        debug!("Zenith store exists, opening...");
+        match load_zenith_store(store, current_time) {
+            Ok(hm) => return Ok(hm),
+            Err(e @ StoreError::TooNew(_)) | Err(e @ StoreError::Io(_)) => return Err(e),
+            Err(e) => {
+                error!("Couldn't load {}: {}", store.display(), e);
+                set_aside(store);
+                e
+            }
         }
+    } else {
+        StoreError::Io(std::io::ErrorKind::NotFound.into())
     };
-    if let Some(previous_stop) = hm.previous_stop {
-        if previous_stop < *current_time {
-            let d = current_time
-                .duration_since(previous_stop)
-                .expect("Current time is before stored time. This should not happen.");
-            let week_ticks = ONE_WEEK / hm.tick.as_secs();
-            for (_k, v) in hm.map.iter_mut() {
-                let data = v.data.to_mut();
-                if data.len() as u64 > week_ticks {
-                    let end = data.len() as u64 - week_ticks;
-                    data.drain(0..end as usize);
-                }
-                let mut dur = d;
-                // add 0s between then and now.
-                let zero_dur = Duration::from_secs(0);
-                while dur > zero_dur + hm.tick {
-                    data.push(0);
-                    dur -= hm.tick;
+    for n in 1..=STORE_BACKUPS {
+        let backup = backup_path(store, n);
+        if !backup.exists() {
+            continue;
+        }
+        match load_zenith_store(&backup, current_time) {
+            Ok(hm) => {
+                let saved = hm
+                    .previous_stop
+                    .map(|t| {
+                        DateTime::<Local>::from(t)
+                            .format("%Y-%m-%d %H:%M:%S")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| "an unknown time".to_string());
+                warn!(
+                    "Recovered history from {} saved at {}",
+                    backup.display(),
+                    saved
+                );
+                return Ok(hm);
+            }
+            Err(e @ StoreError::TooNew(_)) => return Err(e),
+            Err(e @ StoreError::Io(_)) => error!("Couldn't load {}: {}", backup.display(), e),
+            Err(e) => {
+                error!("Couldn't load {}: {}", backup.display(), e);
+                set_aside(&backup);
+            }
+        }
+    }
+    Err(err)
+}
+
+/// Reads the store and its journal as they were last saved. The returned map doesn't write
+/// anything back until `db` is set.
+pub fn open_zenith_store(path: &Path) -> Result<HistogramMap, StoreError> {
+    let mut hm = read_store(path)?;
+    hm.replay_journal(&journal_path(path))?;
+    Ok(hm)
+}
+
+/// Reads the database in `db` as it was last saved, without writing anything back. A store
+/// that is missing or damaged is replaced by the newest backup that reads back intact, and a
+/// database that wasn't compacted yet, as while its recorder runs for the first hour, is read
+/// from the journal alone.
+pub fn open_database(db: &Path) -> Result<HistogramMap, StoreError> {
+    let store = db.join("store");
+    let err = match open_zenith_store(&store) {
+        Err(StoreError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => StoreError::Io(e),
+        Err(e @ StoreError::Corrupt(_)) => e,
+        loaded => return loaded,
+    };
+    for n in 1..=STORE_BACKUPS {
+        let backup = backup_path(&store, n);
+        if !backup.exists() {
+            continue;
+        }
+        match open_zenith_store(&backup) {
+            Ok(hm) => {
+                warn!(
+                    "Read {} instead of {}: {}",
+                    backup.display(),
+                    store.display(),
+                    err
+                );
+                return Ok(hm);
+            }
+            Err(e @ StoreError::TooNew(_)) => return Err(e),
+            Err(e) => warn!("Couldn't read {}: {}", backup.display(), e),
+        }
+    }
+    let journal = journal_path(&store);
+    if matches!(err, StoreError::Io(_)) && journal.exists() {
+        // the tick is replaced by the one in the journal header
+        let mut hm = HistogramMap::empty(
+            Retention::default().memory_window,
+            Duration::from_millis(2000),
+            None,
+        );
+        hm.replay_journal(&journal)?;
+        return Ok(hm);
+    }
+    Err(err)
+}
+
+/// What reading a journal back found, see `check_journal`.
+pub struct JournalCheck {
+    /// Tick of the segments, None without a header.
+    pub tick: Option<Duration>,
+    /// Segments that read back intact, up to the first one that doesn't.
+    pub segments: usize,
+    /// Why the header or the segment after the intact ones can't be read. Replaying the journal
+    /// stops there.
+    pub damaged: Option<String>,
+    /// Bytes of a segment cut short at the end, as by a crash while it was appended.
+    pub torn_bytes: usize,
+}
+
+/// Decodes the header and the segments of the journal at `path`, up to the first one that
+/// can't be read.
+fn read_journal(path: &Path) -> Result<(JournalCheck, Vec<Segment>), StoreError> {
+    let frames = journal::read_frames(path)?;
+    let mut check = JournalCheck {
+        tick: None,
+        segments: 0,
+        damaged: None,
+        torn_bytes: frames.torn_bytes,
+    };
+    let mut frames = frames.frames.into_iter();
+    let Some(header) = frames.next() else {
+        return Ok((check, vec![]));
+    };
+    match parse_format_header(&header) {
+        Some(version) if version > STORE_VERSION => return Err(StoreError::TooNew(version)),
+        Some(STORE_VERSION) => {
+            match bincode::deserialize::<Duration>(&header[STORE_HEADER_LEN..]) {
+                Ok(tick) if !tick.is_zero() => check.tick = Some(tick),
+                _ => check.damaged = Some("the header has no tick".to_string()),
+            }
+        }
+        _ => check.damaged = Some("unknown format".to_string()),
+    }
+    let mut segments = vec![];
+    if check.damaged.is_none() {
+        for frame in frames {
+            match bincode::deserialize(&frame) {
+                Ok(segment) => segments.push(segment),
+                Err(e) => {
+                    check.damaged = Some(format!(
+                        "segment {}: {}: {}",
+                        segments.len() + 1,
+                        DSER_ERROR,
+                        e
+                    ));
+                    break;
                 }
             }
         }
     }
-    Some(hm)
+    check.segments = segments.len();
+    Ok((check, segments))
+}
+
+/// Reads back every segment of the journal at `path` without applying them.
+pub fn check_journal(path: &Path) -> Result<JournalCheck, StoreError> {
+    read_journal(path).map(|(check, _)| check)
+}
+
+/// Reads the store alone, without the segments journaled since it was written.
+pub fn read_store(path: &Path) -> Result<HistogramMap, StoreError> {
+    let data = fs::read(path)?;
+    if let Some(dir) = path.parent() {
+        if let Some(version) = read_configuration(dir).get("version") {
+            debug!("Store was last written by zenith {}", version);
+        }
+    }
+    let mut hm = decode_store(&data)?;
+    hm.db = None;
+    Ok(hm)
+}
+
+pub fn load_zenith_store(
+    path: &Path,
+    current_time: &SystemTime,
+) -> Result<HistogramMap, StoreError> {
+    // need to fill in time between when it was last stored and now, like the sled DB
+    let mut hm = open_zenith_store(path)?;
+    hm.fill_until(current_time);
+    Ok(hm)
 }
 
 impl HistogramMap {
     pub(crate) fn new(dur: Duration, tick: Duration, db: Option<PathBuf>) -> HistogramMap {
         let current_time = SystemTime::now();
+        let host = hostname();
         match db {
             Some(db) => {
                 debug!("Opening DB");
                 let dbfile = db.join("store");
 
-                if dbfile.exists() {
-                    debug!("Zenith store exists, opening...");
-                    load_zenith_store(&dbfile, &current_time)
-                } else {
-                    None
-                }
-                .unwrap_or_else(|| {
-                    if let Err(e) = fs::remove_file(dbfile) {
-                        error!("{}", e);
+                match load_with_backups(&dbfile, &current_time) {
+                    Ok(hm) if hm.host.as_ref().is_some_and(|h| *h != host) => {
+                        warn!(
+                            "{} holds history recorded on {}, not recording history to it",
+                            db.display(),
+                            hm.host.as_deref().unwrap_or_default()
+                        );
+                        let mut other = HistogramMap::empty(dur, tick, None);
+                        other.other_host = hm.host.clone();
+                        other
                     }
-                    debug!("Starting a new database.");
-                    HistogramMap {
-                        map: HashMap::with_capacity(5),
-                        duration: dur,
-                        tick,
-                        db: Some(db),
-                        previous_stop: None,
+                    Ok(mut hm) => {
+                        debug!(
+                            "Loaded {} series using {} KiB",
+                            hm.map.len(),
+                            hm.size_bytes() / 1024
+                        );
+                        hm.db = Some(db);
+                        hm.host = Some(host);
+                        hm.use_tick(tick);
+                        hm
                     }
-                })
+                    Err(StoreError::TooNew(version)) => {
+                        HistogramMap::newer(dur, tick, &db, version)
+                    }
+                    Err(StoreError::Io(e)) if e.kind() != std::io::ErrorKind::NotFound => {
+                        HistogramMap::unreadable(dur, tick, &dbfile, e)
+                    }
+                    Err(_) => {
+                        debug!("Starting a new database.");
+                        let mut hm = HistogramMap::empty(dur, tick, Some(db.clone()));
+                        hm.host = Some(host);
+                        // segments saved before the first compaction
+                        match hm.replay_journal(&journal_path(&dbfile)) {
+                            Err(StoreError::TooNew(version)) => {
+                                // dropped without writing, the journal must stay as it is
+                                hm.db = None;
+                                return HistogramMap::newer(dur, tick, &db, version);
+                            }
+                            Err(e) => error!("{}", e),
+                            Ok(()) => {}
+                        }
+                        hm.fill_until(&current_time);
+                        hm.use_tick(tick);
+                        hm
+                    }
+                }
             }
             None => {
                 debug!("Starting with no DB.");
-                HistogramMap {
-                    map: HashMap::with_capacity(5),
-                    duration: dur,
-                    tick,
-                    db: None,
-                    previous_stop: None,
-                }
+                HistogramMap::empty(dur, tick, None)
             }
         }
     }
 
-    pub fn get_zoomed<'a>(&'a self, name: &HistogramKind, view: &View) -> Option<Histogram<'a>> {
-        let h = self.get(name)?;
-        let h_data = h.data();
-        let h_len = h_data.len();
+    /// A map that leaves `db` alone because a newer zenith wrote it in format `version`.
+    fn newer(dur: Duration, tick: Duration, db: &Path, version: u32) -> HistogramMap {
+        warn!(
+            "{}, not recording history to {}",
+            StoreError::TooNew(version),
+            db.display()
+        );
+        let mut hm = HistogramMap::empty(dur, tick, None);
+        hm.newer_store = Some(version);
+        hm
+    }
 
-        if view.zoom_factor == 1 {
-            let low = h_len - (view.width + view.offset);
-            let high = h_len - view.offset;
-            return Some(Histogram {
-                data: Cow::Borrowed(&h_data[low..high]),
-            });
+    /// A map that leaves the store alone because reading it failed with `e`. Its history would
+    /// be lost if a new store was written over it.
+    fn unreadable(dur: Duration, tick: Duration, store: &Path, e: std::io::Error) -> HistogramMap {
+        let e = format!("{} can't be read: {}", store.display(), e);
+        error!("{}, not recording history to it", e);
+        let mut hm = HistogramMap::empty(dur, tick, None);
+        hm.unreadable_store = Some(e);
+        hm
+    }
+
+    fn empty(dur: Duration, tick: Duration, db: Option<PathBuf>) -> HistogramMap {
+        HistogramMap {
+            map: HashMap::with_capacity(5),
+            duration: dur,
+            tick,
+            db,
+            previous_stop: None,
+            tiers: ROLLUP_TIERS.to_vec(),
+            times: Samples::default(),
+            host: None,
+            top: TopHistory::default(),
+            unsaved: HashMap::new(),
+            unsaved_times: vec![],
+            unsaved_top: vec![],
+            journal_segments: 0,
+            newer_store: None,
+            other_host: None,
+            unreadable_store: None,
+            gone_interfaces: HashMap::new(),
+            viewing: None,
+            viewed_at: None,
+        }
+    }
+
+    /// Opens the database another instance records to without writing to it. New samples are
+    /// ignored, `reload` picks up what the recorder saved since.
+    pub(crate) fn viewer(dur: Duration, tick: Duration, db: PathBuf) -> HistogramMap {
+        let mut hm = HistogramMap::empty(dur, tick, None);
+        hm.viewing = Some(db);
+        hm.reload();
+        hm
+    }
+
+    /// Reads the viewed database again if the recorder saved since it was last read.
+    pub(crate) fn reload(&mut self) -> bool {
+        let Some(db) = self.viewing.clone() else {
+            return false;
+        };
+        let dbfile = db.join("store");
+        let modified = [dbfile.clone(), journal_path(&dbfile)]
+            .iter()
+            .filter_map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+            .max();
+        if modified.is_none() || modified == self.viewed_at {
+            return false;
+        }
+        match open_database(&db) {
+            Ok(mut hm) => {
+                debug!("Reloaded {}", db.display());
+                hm.viewing = Some(db);
+                hm.viewed_at = modified;
+                *self = hm;
+                true
+            }
+            Err(e) => {
+                warn!("Couldn't reload {}: {}", dbfile.display(), e);
+                false
+            }
+        }
+    }
+
+    /// Resamples history loaded at another tick to `tick`. The journal holds segments of the
+    /// old tick, so a new one is started.
+    fn use_tick(&mut self, tick: Duration) {
+        if self.tick == tick {
+            return;
+        }
+        info!(
+            "Resampling history from {}ms to {}ms ticks",
+            self.tick.as_millis(),
+            tick.as_millis()
+        );
+        self.retick(tick);
+        self.compact();
+    }
+
+    /// Converts all raw history to samples of `tick`.
+    fn retick(&mut self, tick: Duration) {
+        let from = self.tick;
+        let newest = self.time_at(0);
+        self.tick = tick;
+        let raw_max = self.raw_max();
+        for (k, h) in self.map.iter_mut() {
+            h.resample(from, tick, raw_max, k.per_tick());
+        }
+        // the resampled ticks are evenly spaced back from the newest sample
+        let len = self.map.values().map(|h| h.data.len()).max().unwrap_or(0);
+        let times: Vec<u64> = match newest {
+            Some(t) => (0..len as u32)
+                .rev()
+                .filter_map(|k| t.checked_sub(tick * k))
+                .map(epoch_millis)
+                .collect(),
+            None => vec![],
+        };
+        self.times = times.into();
+        // which process used the most can't be told apart for the merged or split ticks
+        self.top.clear();
+    }
+
+    /// Applies the configured retention, dropping whatever is older.
+    pub(crate) fn set_retention(&mut self, retention: Retention) {
+        if self.viewing.is_some() {
+            return;
+        }
+        self.duration = retention.memory_window.min(retention.history);
+        self.tiers = retention.tiers();
+        let raw_max = self.raw_max();
+        for h in self.map.values_mut() {
+            h.data.truncate_front(raw_max);
+            for (tier, rollup) in self.tiers.iter().zip(h.rollups.iter_mut()) {
+                rollup.prune(tier.max_buckets());
+            }
+        }
+        self.times.truncate_front(raw_max);
+        self.top.truncate_front(raw_max);
+    }
+
+    /// How far back any series has recorded samples.
+    pub fn recorded_span(&self) -> Option<Duration> {
+        let ticks = self
+            .map
+            .values()
+            .flat_map(|h| {
+                let raw = h.data.len() - h.data.position(|v| v != NO_DATA).unwrap_or(h.data.len());
+                self.tiers
+                    .iter()
+                    .zip(h.rollups.iter())
+                    .map(|(t, r)| r.recorded_len() * t.ticks_per_bucket(self.tick) as usize)
+                    .chain(std::iter::once(raw))
+            })
+            .max()?;
+        u32::try_from(ticks)
+            .ok()
+            .and_then(|t| self.tick.checked_mul(t))
+    }
+
+    /// Records when the samples of the next tick are captured.
+    pub(crate) fn begin_tick(&mut self, time: SystemTime) {
+        if self.viewing.is_some() {
+            return;
+        }
+        if self.db.is_some() {
+            self.unsaved_times.push(time);
+            self.unsaved_top.push(vec![]);
+        }
+        self.push_time(time);
+    }
+
+    fn push_time(&mut self, time: SystemTime) {
+        let raw_max = self.raw_max();
+        self.times.push(epoch_millis(time), 1);
+        if self.times.len() > raw_max + raw_max / 16 {
+            self.times.truncate_front(raw_max);
+        }
+        self.top.push_tick();
+        self.top.truncate_front(self.times.len());
+    }
+
+    /// Records the processes using the most of each resource during the current tick.
+    pub(crate) fn record_top(&mut self, consumers: Vec<Consumer>) {
+        if self.viewing.is_some() {
+            return;
+        }
+        self.top.set_last(&consumers);
+        if let Some(last) = self.unsaved_top.last_mut() {
+            *last = consumers;
+        }
+    }
+
+    /// The processes using the most of each resource in the `ticks` ticks starting `age` ticks
+    /// before the newest sample. Empty beyond the raw samples.
+    pub fn top_at(&self, age: usize, ticks: usize) -> Vec<Consumer> {
+        self.top.at(age, ticks)
+    }
+
+    /// Capture time of the sample `age` ticks before the newest one. Beyond the recorded times
+    /// it is estimated from the tick.
+    pub fn time_at(&self, age: usize) -> Option<SystemTime> {
+        let n = self.times.len();
+        if age < n {
+            return self.times.get(n - 1 - age).map(from_epoch_millis);
+        }
+        let (known, known_age) = match self.times.get(0) {
+            Some(t) => (from_epoch_millis(t), n - 1),
+            None => (self.previous_stop?, 0),
+        };
+        let ticks = u32::try_from(age - known_age).ok()?;
+        known.checked_sub(self.tick.checked_mul(ticks)?)
+    }
+
+    /// Marks the ticks between the last save and `until` as gaps in every histogram.
+    fn fill_until(&mut self, until: &SystemTime) {
+        let Some(previous_stop) = self.previous_stop else {
+            return;
+        };
+        let Ok(d) = until.duration_since(previous_stop) else {
+            return;
+        };
+        let missed = (d.as_millis() / self.tick.as_millis().max(1)) as u64;
+        let raw_max = self.raw_max();
+        let kept = missed.min(raw_max as u64);
+        for i in missed - kept + 1..=missed {
+            self.push_time(previous_stop + self.tick * i as u32);
+        }
+        for (_k, v) in self.map.iter_mut() {
+            v.push(NO_DATA, missed, raw_max, &self.tiers, self.tick);
+            v.data.truncate_front(raw_max);
+        }
+    }
+
+    /// Applies the segments appended to the journal since the store was last rewritten.
+    fn replay_journal(&mut self, path: &Path) -> Result<(), StoreError> {
+        let (check, segments) = read_journal(path)?;
+        if check.torn_bytes > 0 {
+            warn!(
+                "Discarding {} bytes of incomplete journal segment in {}",
+                check.torn_bytes,
+                path.display()
+            );
+            // new segments can't be appended after the damaged one
+            self.journal_segments = JOURNAL_COMPACT_SEGMENTS;
+        }
+        if let Some(e) = &check.damaged {
+            error!("Ignoring the rest of {}: {}", path.display(), e);
+            self.journal_segments = JOURNAL_COMPACT_SEGMENTS;
+        }
+        // the segments are replayed at the tick they were recorded at
+        if let Some(tick) = check.tick.filter(|t| *t != self.tick) {
+            self.retick(tick);
+        }
+        for segment in segments {
+            // already part of the store if compaction was interrupted before the journal was removed
+            if self.previous_stop.is_some_and(|p| segment.stop <= p) {
+                continue;
+            }
+            let ticks = segment.samples.iter().map(|(_, v)| v.len()).max();
+            let ticks = ticks.unwrap_or(0) as u32;
+            if let Some(start) = segment.stop.checked_sub(self.tick * ticks) {
+                self.fill_until(&start);
+            }
+            for (k, values) in segment.samples {
+                for v in values {
+                    self.push_value(&k, v);
+                }
+            }
+            for (i, t) in segment.times.into_iter().enumerate() {
+                self.push_time(t);
+                if let Some(top) = segment.top.get(i) {
+                    self.top.set_last(top);
+                }
+            }
+            self.previous_stop = Some(segment.stop);
+            self.journal_segments += 1;
+        }
+        self.unsaved.clear();
+        self.unsaved_times.clear();
+        self.unsaved_top.clear();
+        Ok(())
+    }
+
+    /// Number of raw samples kept per histogram.
+    fn raw_max(&self) -> usize {
+        (self.duration.as_millis() / self.tick.as_millis().max(1)) as usize
+    }
+
+    /// Coarsest tier whose buckets are no wider than a chart column at this zoom.
+    fn tier_for_zoom(&self, zoom_factor: u32) -> Option<usize> {
+        self.tiers
+            .iter()
+            .rposition(|t| t.ticks_per_bucket(self.tick) <= zoom_factor as u64)
+    }
+
+    pub fn get_zoomed(&self, name: &HistogramKind, view: &View) -> Option<Histogram> {
+        let h = self.get(name)?;
+
+        if let Some(t) = self.tier_for_zoom(view.zoom_factor) {
+            let rollup = h.rollups.get(t)?;
+            let per_column = (view.zoom_factor as u64 / self.tiers[t].ticks_per_bucket(self.tick))
+                .max(1) as usize;
+            let len = rollup.len();
+            let end = len.saturating_sub(per_column * view.offset);
+            let start = end.saturating_sub(per_column * view.width);
+            let buckets = rollup.range(start, end);
+            let new_data: Vec<_> = buckets
+                .chunks(per_column)
+                .map(|set| {
+                    let mut b = Bucket::default();
+                    set.iter().for_each(|s| b.merge(s));
+                    b.avg()
+                })
+                .collect();
+            return Some(Histogram::view(new_data));
+        }
+
+        let h_len = h.data.len();
+
+        if view.zoom_factor == 1 {
+            let low = h_len.saturating_sub(view.width + view.offset);
+            let high = h_len.saturating_sub(view.offset);
+            return Some(Histogram::view(h.data.range(low, high)));
         }
 
         let zf = view.zoom_factor as usize;
@@ -182,31 +1488,239 @@ impl HistogramMap {
             h_len.saturating_sub((view.width + view.offset) * zf + view.update_number as usize);
         let end = h_len.saturating_sub(zf * view.offset);
 
-        let new_data: Vec<_> = h_data[start..end]
+        let new_data: Vec<_> = h
+            .data
+            .range(start, end)
             .chunks(zf)
-            .map(|set| set.iter().sum::<u64>() / view.zoom_factor as u64)
+            .map(|set| {
+                let mut b = Bucket::default();
+                set.iter().for_each(|v| b.add(*v, 1));
+                b.avg()
+            })
             .collect();
 
-        Some(Histogram {
-            data: Cow::Owned(new_data),
-        })
+        Some(Histogram::view(new_data))
+    }
+
+    /// Ticks before the newest sample of the newest one in `column` of a chart of `view`,
+    /// counting columns from the newest one shown.
+    pub fn column_age(&self, view: &View, column: usize) -> usize {
+        let zf = view.zoom_factor.max(1) as usize;
+        (view.offset + column) * zf + self.hidden_ticks(view)
+    }
+
+    /// Column of a chart of `view` holding the sample `age` ticks before the newest one, 0 if
+    /// it is newer than the chart shows.
+    pub fn age_column(&self, view: &View, age: usize) -> usize {
+        let zf = view.zoom_factor.max(1) as usize;
+        age.saturating_sub(self.column_age(view, 0)) / zf
+    }
+
+    /// Newest ticks that are averaged into a column not shown yet, see `get_zoomed`.
+    fn hidden_ticks(&self, view: &View) -> usize {
+        if view.zoom_factor > 1 && self.tier_for_zoom(view.zoom_factor).is_none() {
+            view.update_number as usize
+        } else {
+            0
+        }
+    }
+
+    /// Columns `newest` to `oldest` of a chart of `view` as they are shown.
+    pub fn get_columns(
+        &self,
+        name: &HistogramKind,
+        view: &View,
+        newest: usize,
+        oldest: usize,
+    ) -> Option<Histogram> {
+        let width = oldest.saturating_sub(newest) + 1;
+        let columns = View {
+            width,
+            offset: view.offset + newest,
+            ..*view
+        };
+        let h = self.get_zoomed(name, &columns)?;
+        // the column still being averaged isn't shown
+        Some(Histogram::view(h.data.range(0, width)))
+    }
+
+    /// Statistics of the samples in columns `newest` to `oldest` of a chart of `view`, as counted
+    /// by `get_columns`. They are taken over the raw samples the columns cover while those are
+    /// kept, so zooming out doesn't average spikes away. Beyond them the columns come from a
+    /// rollup tier, see `Stats::of_buckets`.
+    pub fn column_stats(
+        &self,
+        name: &HistogramKind,
+        view: &View,
+        newest: usize,
+        oldest: usize,
+    ) -> Option<Stats> {
+        let h = self.get(name)?;
+        let raw_len = h.data.len();
+        let zf = view.zoom_factor.max(1) as usize;
+        // ticks back from the newest sample to the newest one covered and past the oldest one
+        let (first, end) = match self.tier_for_zoom(view.zoom_factor) {
+            Some(t) => {
+                let rollup = h.rollups.get(t)?;
+                let per_bucket = self.tiers[t].ticks_per_bucket(self.tick) as usize;
+                let per_column = (zf / per_bucket).max(1);
+                // buckets back from the newest one, as get_zoomed picks them
+                let newest_bucket = per_column * (view.offset + newest);
+                let past_oldest = per_column * (view.offset + oldest + 1);
+                let newest_ticks = if rollup.pending_ticks > 0 {
+                    rollup.pending_ticks as usize
+                } else {
+                    per_bucket
+                };
+                let first = newest_bucket
+                    .checked_sub(1)
+                    .map_or(0, |j| newest_ticks + j * per_bucket);
+                let end = newest_ticks + (past_oldest - 1) * per_bucket;
+                if end > raw_len {
+                    let len = rollup.len();
+                    let buckets = rollup.range(
+                        len.saturating_sub(past_oldest),
+                        len.saturating_sub(newest_bucket),
+                    );
+                    return Stats::of_buckets(&buckets);
+                }
+                (first, end)
+            }
+            None => (
+                self.column_age(view, newest),
+                self.column_age(view, oldest) + zf,
+            ),
+        };
+        let samples = h
+            .data
+            .range(raw_len.saturating_sub(end), raw_len.saturating_sub(first));
+        Histogram::view(samples).stats()
+    }
+
+    /// Number of ticks between the newest sample and `time`, estimated from the tick.
+    pub fn age_at(&self, time: SystemTime) -> Option<usize> {
+        let newest = self.time_at(0)?;
+        let d = newest.duration_since(time).unwrap_or_default();
+        Some((d.as_millis() as f64 / self.tick.as_millis().max(1) as f64).round() as usize)
     }
 
-    pub fn get(&'_ self, name: &HistogramKind) -> Option<&'_ Histogram<'_>> {
+    /// Zoom factor and offset at which a chart of `width` columns shows `from` to `to`. Ends
+    /// after the newest sample are shown at the present. None before the first sample.
+    pub fn window_view(
+        &self,
+        from: SystemTime,
+        to: SystemTime,
+        width: usize,
+    ) -> Option<(u32, usize)> {
+        let from_age = self.age_at(from)?;
+        let to_age = self.age_at(to)?;
+        let ticks = from_age.saturating_sub(to_age).max(1);
+        let zoom = ticks
+            .div_ceil(width.max(1))
+            .clamp(1, self.max_zoom_factor(width) as usize);
+        Some((zoom as u32, to_age / zoom))
+    }
+
+    pub fn get(&self, name: &HistogramKind) -> Option<&Histogram> {
         self.map.get(name)
     }
 
+    /// Memory used by the samples of all series.
+    pub fn size_bytes(&self) -> usize {
+        self.map.values().map(Histogram::size_bytes).sum()
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = &HistogramKind> {
+        self.map.keys()
+    }
+
+    /// Raw samples of `h` with the number of ticks before the newest one and the time each was
+    /// recorded, newest first.
+    fn aged_samples<'a>(
+        &'a self,
+        h: &Histogram,
+    ) -> impl Iterator<Item = (usize, SystemTime, u64)> + 'a {
+        // decoded once rather than for every sample through time_at
+        let times = self.times.to_vec();
+        h.data()
+            .into_iter()
+            .rev()
+            .enumerate()
+            .filter(|(_, v)| *v != NO_DATA)
+            .filter_map(move |(age, v)| {
+                let t = match times.len().checked_sub(age + 1) {
+                    Some(j) => from_epoch_millis(times[j]),
+                    None => self.time_at(age)?,
+                };
+                Some((age, t, v))
+            })
+    }
+
+    /// Samples of `name` with the time each was recorded, oldest first. Gaps are left out.
+    /// History older than the raw samples is given at the resolution it is kept at: the mean of
+    /// each rollup bucket of the finest tier that covers it, stamped with the time of its
+    /// newest tick. A bucket reaching past the finer samples replaces those it overlaps.
+    pub fn timestamped(&self, name: &HistogramKind) -> Vec<(SystemTime, u64)> {
+        let Some(h) = self.get(name) else {
+            return vec![];
+        };
+        let mut samples: Vec<_> = self.aged_samples(h).collect();
+        // ticks back from the newest sample that a finer resolution covers
+        let mut covered = h.data.len();
+        for (tier, rollup) in self.tiers.iter().zip(h.rollups.iter()) {
+            let per_bucket = tier.ticks_per_bucket(self.tick) as usize;
+            // ticks in the newest bucket, the one still being filled if there is one
+            let newest = if rollup.pending_ticks > 0 {
+                rollup.pending_ticks as usize
+            } else {
+                per_bucket
+            };
+            let buckets = rollup.range(0, rollup.len());
+            for (j, b) in buckets.iter().rev().enumerate() {
+                // ticks back to the newest tick of the bucket and past its oldest one
+                let age = j.checked_sub(1).map_or(0, |j| newest + j * per_bucket);
+                let end = newest + j * per_bucket;
+                if end <= covered {
+                    continue;
+                }
+                if age < covered {
+                    samples.retain(|(a, _, _)| *a < age);
+                }
+                if b.count > 0 {
+                    if let Some(t) = self.time_at(age) {
+                        samples.push((age, t, b.avg()));
+                    }
+                }
+            }
+            covered = covered.max(newest + buckets.len().saturating_sub(1) * per_bucket);
+        }
+        samples.reverse();
+        samples.into_iter().map(|(_, t, v)| (t, v)).collect()
+    }
+
     pub(crate) fn add_value_to(&mut self, name: &HistogramKind, val: u64) {
+        if self.viewing.is_some() {
+            return;
+        }
+        if self.db.is_some() {
+            self.unsaved.entry(name.clone()).or_default().push(val);
+        }
+        self.push_value(name, val);
+        debug!("Adding {} to {:?} chart.", val, name);
+    }
+
+    fn push_value(&mut self, name: &HistogramKind, val: u64) {
+        let raw_max = self.raw_max();
         let h = if let Some(h) = self.map.get_mut(name) {
             h
         } else {
             let size = (self.duration.as_secs() / self.tick.as_secs()) as usize; //smallest has to be >= 1000ms
+            let tiers = self.tiers.len();
             self.map
                 .entry(name.clone())
-                .or_insert_with(|| Histogram::new(size))
+                .or_insert_with(|| Histogram::new(size, tiers))
         };
-        h.data.to_mut().push(val);
-        debug!("Adding {} to {:?} chart.", val, name);
+        h.push(val, 1, raw_max, &self.tiers, self.tick);
     }
 
     pub fn hist_duration(&self, width: usize, zoom_factor: u32) -> chrono::Duration {
@@ -216,72 +1730,259 @@ impl HistogramMap {
         .expect("Unexpectedly large duration was out of range.")
     }
 
+    /// Number of ticks of history available, counting the rollup tiers.
     pub fn histograms_width(&self) -> Option<usize> {
-        self.map.iter().next().map(|(_k, h)| h.data.len())
+        self.map.iter().next().map(|(_k, h)| {
+            self.tiers
+                .iter()
+                .zip(h.rollups.iter())
+                .map(|(t, r)| r.len() * t.ticks_per_bucket(self.tick) as usize)
+                .fold(h.data.len(), usize::max)
+        })
+    }
+
+    /// Zoom factor at which a chart of `width` columns covers the longest tier.
+    pub fn max_zoom_factor(&self, width: usize) -> u32 {
+        let longest = self
+            .tiers
+            .iter()
+            .map(|t| t.retention)
+            .fold(self.duration, Duration::max);
+        let ticks = longest.as_millis() / self.tick.as_millis().max(1);
+        (ticks / width.max(1) as u128).clamp(1, u32::MAX as u128) as u32
     }
 
+    /// Appends the samples added since the last save to the journal, rewriting the
+    /// whole store once enough segments have accumulated.
     pub(crate) fn save_histograms(&mut self) {
-        if let Some(db) = &self.db {
-            debug!("Saving Histograms...");
-            self.previous_stop = Some(SystemTime::now());
-            let dbfile = db.join("store");
-            let database_open = fs::OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(dbfile);
-            match database_open {
-                Ok(database) => {
-                    let mut gz = GzEncoder::new(database, Compression::default());
-                    gz.write_all(&bincode::serialize(self).expect(SER_ERROR))
-                        .expect("Failed to compress/write to file.");
-                    match gz.finish() {
-                        Ok(_r) => {
-                            debug!("Write Finished.");
-                        }
-                        Err(_e) => {
-                            error!("Couldn't complete database write.");
-                        }
-                    };
-                    let configuration = db.join(".configuration");
-                    let mut configuration = fs::OpenOptions::new()
-                        .create(true)
-                        .write(true)
-                        .truncate(true)
-                        .open(configuration)
-                        .expect("Couldn't open Configuration");
-                    configuration
-                        .write_all(format!("version={:}\n", env!("CARGO_PKG_VERSION")).as_bytes())
-                        .expect("Failed to write file.");
+        if self.db.is_none() {
+            return;
+        }
+        if self.journal_segments >= JOURNAL_COMPACT_SEGMENTS {
+            self.compact();
+        } else {
+            self.append_segment();
+        }
+    }
+
+    fn append_segment(&mut self) {
+        let Some(db) = &self.db else {
+            return;
+        };
+        debug!("Appending journal segment...");
+        let stop = SystemTime::now();
+        let segment = Segment {
+            stop,
+            samples: self.unsaved.drain().collect(),
+            times: std::mem::take(&mut self.unsaved_times),
+            top: std::mem::take(&mut self.unsaved_top),
+        };
+        let payload = bincode::serialize(&segment).expect(SER_ERROR);
+        let path = journal_path(&db.join("store"));
+        let new_journal = fs::metadata(&path).map_or(true, |m| m.len() == 0);
+        let result = if new_journal {
+            journal::append_frame(&path, &journal_header(self.tick))
+        } else {
+            Ok(())
+        };
+        if let Err(e) = result.and_then(|_| journal::append_frame(&path, &payload)) {
+            exit_with_message!(
+                format!("Couldn't write to {}, error: {}", path.to_string_lossy(), e),
+                1
+            );
+        }
+        self.previous_stop = Some(stop);
+        self.journal_segments += 1;
+    }
+
+    /// Rewrites the whole store and starts a new journal.
+    pub(crate) fn compact(&mut self) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        debug!("Saving Histograms...");
+        self.previous_stop = Some(SystemTime::now());
+        if let Err(e) = self.write_store(&db) {
+            exit_with_message!(
+                format!(
+                    "Couldn't write to {}, error: {}",
+                    db.join("store").to_string_lossy(),
+                    e
+                ),
+                1
+            );
+        }
+    }
+
+    /// Writes the history to the store in `db` as it is, for maintenance while no zenith
+    /// records to it.
+    pub fn rewrite(&mut self, db: &Path) -> std::io::Result<()> {
+        self.write_store(db)
+    }
+
+    /// Replaces the store in `db` with the whole history and removes the journal.
+    fn write_store(&mut self, db: &Path) -> std::io::Result<()> {
+        let dbfile = db.join("store");
+        let tmpfile = db.join("store.tmp");
+        let mut database = fs::File::create(&tmpfile)?;
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(&bincode::serialize(self).expect(SER_ERROR))?;
+        let payload = gz.finish()?;
+        database.write_all(&format_header())?;
+        database.write_all(&journal::checksum(&payload).to_le_bytes())?;
+        database.write_all(&payload)?;
+        database.sync_all()?;
+        rotate_backups(&dbfile)?;
+        // the rename is atomic, a crash leaves either the old or the new store
+        fs::rename(&tmpfile, &dbfile)?;
+        debug!("Write Finished.");
+        if let Err(e) = fs::remove_file(journal_path(&dbfile)) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                error!("Couldn't remove journal: {}", e);
+            }
+        }
+        self.unsaved.clear();
+        self.unsaved_times.clear();
+        self.unsaved_top.clear();
+        self.journal_segments = 0;
+        fs::write(
+            db.join(".configuration"),
+            format!(
+                "version={:}\nformat={:}\n",
+                env!("CARGO_PKG_VERSION"),
+                STORE_VERSION
+            ),
+        )
+    }
+
+    /// Drops the samples captured before `cutoff`. Raw samples become gaps so the series keep
+    /// their length, older rollup buckets are removed.
+    pub fn prune_before(&mut self, cutoff: SystemTime) {
+        let keep = match self.time_at(0).map(|t| t.duration_since(cutoff)) {
+            Some(Ok(d)) => (d.as_millis() / self.tick.as_millis().max(1)) as usize + 1,
+            _ => 0,
+        };
+        for h in self.map.values_mut() {
+            let len = h.data.len();
+            if keep < len {
+                let mut data = vec![NO_DATA; len - keep];
+                data.extend(h.data.range(len - keep, len));
+                h.data = data.into();
+            }
+            for (tier, rollup) in self.tiers.iter().zip(h.rollups.iter_mut()) {
+                let buckets = keep.div_ceil(tier.ticks_per_bucket(self.tick) as usize);
+                rollup.prune(buckets.saturating_sub(usize::from(rollup.pending_ticks > 0)));
+                if buckets == 0 {
+                    rollup.pending = Bucket::default();
+                    rollup.pending_ticks = 0;
                 }
-                Err(e) => {
-                    exit_with_message!(
-                        format!(
-                            "Couldn't write to {}, error: {}",
-                            db.join("store").to_string_lossy(),
-                            e.to_string()
-                        ),
-                        1
-                    );
+            }
+        }
+        self.times.truncate_front(keep);
+        self.top.truncate_front(keep);
+    }
+
+    /// Removes a series and all of its history.
+    pub fn drop_series(&mut self, name: &HistogramKind) -> bool {
+        self.unsaved.remove(name);
+        self.map.remove(name).is_some()
+    }
+
+    /// Adds gaps to the series of the network interfaces not in `present`, so they stay in
+    /// step with the others while the interface may come back. Once their raw window holds
+    /// nothing but gaps they are dropped, or the veths of stopped containers would pile up.
+    pub(crate) fn add_gone_interfaces(&mut self, present: &HashSet<&str>) {
+        if self.viewing.is_some() {
+            return;
+        }
+        let gone: HashSet<String> = self
+            .map
+            .keys()
+            .filter_map(|k| k.interface())
+            .filter(|i| !present.contains(i))
+            .map(str::to_string)
+            .collect();
+        self.gone_interfaces.retain(|i, _| gone.contains(i));
+        let raw_max = self.raw_max();
+        for name in gone {
+            let kinds = HistogramKind::of_interface(&name);
+            let missed = match self.gone_interfaces.get(&name) {
+                Some(missed) => missed + 1,
+                // it may have gone before zenith started
+                None => kinds
+                    .iter()
+                    .filter_map(|k| self.map.get(k))
+                    .map(|h| h.gaps_since_sample())
+                    .min()
+                    .unwrap_or(raw_max)
+                    .saturating_add(1),
+            };
+            if missed >= raw_max {
+                debug!("Dropping the series of network interface {}", name);
+                for k in kinds.iter() {
+                    self.drop_series(k);
                 }
+                self.gone_interfaces.remove(&name);
+                continue;
             }
+            for k in kinds.iter() {
+                if self.map.contains_key(k) {
+                    self.add_value_to(k, NO_DATA);
+                }
+            }
+            self.gone_interfaces.insert(name, missed);
         }
     }
 
+    pub fn tiers(&self) -> &[RollupTier] {
+        &self.tiers
+    }
+
     pub fn writes_db_store(&self) -> bool {
         self.db.is_some()
     }
+
+    /// Database shown read-only when started with `--view`.
+    pub fn viewing(&self) -> Option<&Path> {
+        self.viewing.as_deref()
+    }
+
+    /// Time of the newest sample that was saved or loaded.
+    pub fn previous_stop(&self) -> Option<SystemTime> {
+        self.previous_stop
+    }
+
+    /// Host the history was recorded on, unknown for stores of older zenith.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// Host of the store that was left alone because it records to it.
+    pub fn other_host(&self) -> Option<&str> {
+        self.other_host.as_deref()
+    }
+
+    /// Format version of the store that was left alone because a newer zenith wrote it.
+    pub fn newer_store(&self) -> Option<u32> {
+        self.newer_store
+    }
+
+    /// Why the store that was left alone couldn't be read.
+    pub fn unreadable_store(&self) -> Option<&str> {
+        self.unreadable_store.as_deref()
+    }
 }
 
 impl Drop for HistogramMap {
     fn drop(&mut self) {
-        self.save_histograms();
+        self.compact();
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metrics::top::TopMetric;
     use std::time::Duration;
 
     #[test]
@@ -305,7 +2006,7 @@ mod tests {
 
         let hist = hm.get(&HistogramKind::Cpu).unwrap();
         let data = hist.data();
-        assert!(data.len() > 0);
+        assert!(!data.is_empty());
         assert_eq!(data[data.len() - 3], 50);
         assert_eq!(data[data.len() - 2], 75);
         assert_eq!(data[data.len() - 1], 100);
@@ -387,6 +2088,7 @@ mod tests {
             update_number: 0,
             width: 10,
             offset: 0,
+            cursor: None,
         };
 
         assert!(hm.get_zoomed(&HistogramKind::Cpu, &view).is_none());
@@ -408,6 +2110,7 @@ mod tests {
             update_number: 0,
             width: 5,
             offset: 0,
+            cursor: None,
         };
 
         let zoomed = hm.get_zoomed(&HistogramKind::Cpu, &view);
@@ -440,6 +2143,7 @@ mod tests {
             update_number: 0,
             width: 5,
             offset: 2,
+            cursor: None,
         };
 
         let zoomed = hm.get_zoomed(&HistogramKind::Cpu, &view);
@@ -454,6 +2158,120 @@ mod tests {
         assert_eq!(h_data[h_data.len() - 5], 13);
     }
 
+    #[test]
+    fn test_histogram_stats() {
+        let mut data = vec![NO_DATA];
+        data.extend(1..=100);
+        let stats = Histogram::view(data).stats().unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                min: 1,
+                max: 100,
+                mean: 50.5,
+                p50: 50,
+                p95: 95,
+                p99: 99,
+            }
+        );
+        assert_eq!(Histogram::view(vec![7]).stats().unwrap().p99, 7);
+        assert!(Histogram::view(vec![NO_DATA, NO_DATA]).stats().is_none());
+    }
+
+    #[test]
+    fn test_histogram_map_get_columns() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let mut hm = HistogramMap::new(dur, tick, None);
+        for i in 0..20 {
+            hm.add_value_to(&HistogramKind::Cpu, i as u64);
+        }
+
+        // the newest tick is averaged into a column that isn't shown yet
+        let view = View {
+            zoom_factor: 2,
+            update_number: 1,
+            width: 5,
+            offset: 0,
+            cursor: None,
+        };
+        let shown = hm.get_zoomed(&HistogramKind::Cpu, &view).unwrap().data();
+        let columns = hm.get_columns(&HistogramKind::Cpu, &view, 1, 2).unwrap();
+        assert_eq!(columns.data(), &shown[2..4]);
+        assert_eq!(hm.column_age(&view, 0), 1);
+        assert_eq!(hm.column_age(&view, 1), 3);
+        assert_eq!(hm.age_column(&view, 3), 1);
+        assert_eq!(hm.age_column(&view, 4), 1);
+        assert_eq!(hm.age_column(&view, 0), 0);
+
+        let view = View {
+            zoom_factor: 1,
+            update_number: 0,
+            ..view
+        };
+        let columns = hm.get_columns(&HistogramKind::Cpu, &view, 0, 3).unwrap();
+        assert_eq!(columns.data(), &[16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn test_histogram_map_column_stats() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let mut hm = HistogramMap::new(dur, tick, None);
+        // three minutes of a spike to 100 every tenth tick, otherwise 10 or 20
+        for i in 0..180u64 {
+            let v = if i % 10 == 0 { 100 } else { 10 + 10 * (i % 2) };
+            hm.add_value_to(&HistogramKind::Cpu, v);
+        }
+
+        // zoomed out the columns average the spikes away, the statistics keep them
+        let view = View {
+            zoom_factor: 5,
+            update_number: 0,
+            width: 10,
+            offset: 0,
+            cursor: None,
+        };
+        let columns = hm.get_columns(&HistogramKind::Cpu, &view, 0, 3).unwrap();
+        assert!(columns.recorded().all(|v| v < 100));
+        let stats = hm.column_stats(&HistogramKind::Cpu, &view, 0, 3).unwrap();
+        assert_eq!((stats.min, stats.max), (10, 100));
+        assert_eq!(stats.p50, 20);
+
+        // minutes older than the raw samples come from the rollup, with its minimum and maximum
+        let view = View {
+            zoom_factor: 60,
+            ..view
+        };
+        let stats = hm.column_stats(&HistogramKind::Cpu, &view, 1, 2).unwrap();
+        assert_eq!((stats.min, stats.max), (10, 100));
+        assert_eq!(stats.mean, 24.0);
+    }
+
+    #[test]
+    fn test_histogram_map_gone_interfaces() {
+        let mut hm = HistogramMap::new(Duration::from_secs(10), Duration::from_secs(1), None);
+        let eth0 = HistogramKind::NetIfaceRx("eth0".to_string());
+        let veth = HistogramKind::NetIfaceRx("veth1".to_string());
+        hm.add_value_to(&eth0, 1);
+        hm.add_value_to(&veth, 2);
+
+        // the gone interface keeps in step until its raw window only holds gaps
+        let present = HashSet::from(["eth0"]);
+        for _ in 0..9 {
+            hm.add_value_to(&eth0, 1);
+            hm.add_gone_interfaces(&present);
+        }
+        let data = hm.get(&veth).unwrap().data();
+        assert_eq!(data.len(), hm.get(&eth0).unwrap().data().len());
+        assert_eq!(data[0], 2);
+        assert!(data[1..].iter().all(|v| *v == NO_DATA));
+        hm.add_value_to(&eth0, 1);
+        hm.add_gone_interfaces(&present);
+        assert!(hm.get(&veth).is_none());
+        assert!(hm.get(&eth0).is_some());
+    }
+
     #[test]
     fn test_histogram_map_get_zoomed_with_zoom() {
         let dur = Duration::from_secs(60);
@@ -470,6 +2288,7 @@ mod tests {
             update_number: 0,
             width: 3,
             offset: 0,
+            cursor: None,
         };
 
         let zoomed = hm.get_zoomed(&HistogramKind::Cpu, &view);
@@ -481,6 +2300,454 @@ mod tests {
         assert_eq!(h_data[h_data.len() - 3], 55);
     }
 
+    #[test]
+    fn test_histogram_map_rollup_buckets() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let mut hm = HistogramMap::new(dur, tick, None);
+
+        // two full minutes and a partial one
+        for i in 0..150 {
+            hm.add_value_to(&HistogramKind::Cpu, i % 60);
+        }
+
+        let rollup = &hm.get(&HistogramKind::Cpu).unwrap().rollups[0];
+        assert_eq!(rollup.buckets.len(), 2);
+        let first = rollup.range(0, 1)[0];
+        assert_eq!(first.min, 0);
+        assert_eq!(first.max, 59);
+        assert_eq!(first.avg(), 29);
+        assert_eq!(rollup.pending_ticks, 30);
+        assert_eq!(rollup.len(), 3);
+    }
+
+    #[test]
+    fn test_histogram_map_rollup_retention() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(60);
+        let mut hm = HistogramMap::new(dur, tick, None);
+        let h = hm
+            .map
+            .entry(HistogramKind::Mem)
+            .or_insert_with(|| Histogram::new(0, ROLLUP_TIERS.len()));
+        // a bit over a year at one sample per minute
+        h.push(1, 60 * 24 * 400, 1, &hm.tiers, tick);
+
+        let minutes = &h.rollups[0];
+        assert_eq!(minutes.buckets.len(), ROLLUP_TIERS[0].max_buckets());
+        let hours = &h.rollups[1];
+        assert_eq!(hours.buckets.len(), ROLLUP_TIERS[1].max_buckets());
+        assert!(h.data().len() <= 2);
+    }
+
+    #[test]
+    fn test_histogram_map_get_zoomed_reads_rollup() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let mut hm = HistogramMap::new(dur, tick, None);
+
+        // one minute at 10, one minute at 30
+        for _ in 0..60 {
+            hm.add_value_to(&HistogramKind::Cpu, 10);
+        }
+        for _ in 0..60 {
+            hm.add_value_to(&HistogramKind::Cpu, 30);
+        }
+
+        let view = View {
+            zoom_factor: 60,
+            update_number: 0,
+            width: 5,
+            offset: 0,
+            cursor: None,
+        };
+        let zoomed = hm.get_zoomed(&HistogramKind::Cpu, &view).unwrap();
+        assert_eq!(zoomed.data(), &[10, 30]);
+
+        let view = View {
+            zoom_factor: 120,
+            ..view
+        };
+        let zoomed = hm.get_zoomed(&HistogramKind::Cpu, &view).unwrap();
+        assert_eq!(zoomed.data(), &[20]);
+    }
+
+    #[test]
+    fn test_histogram_map_max_zoom_factor() {
+        let dur = Duration::from_secs(60 * 60);
+        let tick = Duration::from_secs(2);
+        let hm = HistogramMap::new(dur, tick, None);
+
+        // a year at 2s ticks spread over 100 columns
+        assert_eq!(hm.max_zoom_factor(100), 60 * 60 * 24 * 365 / 2 / 100);
+    }
+
+    fn test_db_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("zenith-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_histogram_map_journal_replay() {
+        let db = test_db_dir("journal");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        hm.add_value_to(&HistogramKind::Cpu, 10);
+        hm.add_value_to(&HistogramKind::Cpu, 20);
+        hm.save_histograms();
+        hm.add_value_to(&HistogramKind::Cpu, 30);
+        hm.save_histograms();
+        assert!(!db.join("store").exists());
+        // simulate a crash, nothing is written on drop
+        hm.db = None;
+        drop(hm);
+
+        let hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        let data = hm.get(&HistogramKind::Cpu).unwrap().data();
+        assert_eq!(&data[data.len() - 3..], &[10, 20, 30]);
+        drop(hm);
+
+        // dropping compacts into the store and removes the journal
+        assert!(db.join("store").exists());
+        assert!(!db.join("journal").exists());
+        let hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        let data = hm.get(&HistogramKind::Cpu).unwrap().data();
+        assert_eq!(&data[data.len() - 3..], &[10, 20, 30]);
+        drop(hm);
+
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_journal_replay_at_its_tick() {
+        let db = test_db_dir("journal-tick");
+        let dur = Duration::from_secs(600);
+        let slow = Duration::from_secs(5);
+
+        let mut hm = HistogramMap::new(dur, slow, Some(db.clone()));
+        let t0 = SystemTime::now() - slow * 3;
+        for i in 0..3 {
+            hm.begin_tick(t0 + slow * i);
+            hm.add_value_to(&HistogramKind::Mem, 40);
+        }
+        hm.save_histograms();
+        hm.db = None;
+        drop(hm);
+
+        // reopened at a faster tick the journal is replayed at its own and resampled
+        let tick = Duration::from_secs(1);
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert_eq!(hm.tick, tick);
+        assert_eq!(hm.get(&HistogramKind::Mem).unwrap().recorded().count(), 15);
+        assert!(db.join("store").exists());
+        assert!(!db.join("journal").exists());
+        hm.db = None;
+        drop(hm);
+
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_top_consumers() {
+        let db = test_db_dir("top");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let consumer = |pid, value| Consumer {
+            metric: TopMetric::Cpu,
+            pid,
+            name: format!("p{}", pid),
+            value,
+        };
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        let t0 = SystemTime::now() - tick * 2;
+        hm.begin_tick(t0);
+        hm.add_value_to(&HistogramKind::Cpu, 80);
+        hm.record_top(vec![consumer(1, 70.0), consumer(2, 10.0)]);
+        hm.begin_tick(t0 + tick);
+        hm.add_value_to(&HistogramKind::Cpu, 5);
+        hm.record_top(vec![consumer(3, 5.0)]);
+        hm.save_histograms();
+        // simulate a crash, the consumers come back from the journal
+        hm.db = None;
+        drop(hm);
+
+        let hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert_eq!(hm.top_at(0, 1), vec![consumer(3, 5.0)]);
+        assert_eq!(hm.top_at(1, 1), vec![consumer(1, 70.0), consumer(2, 10.0)]);
+        drop(hm);
+
+        let hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert_eq!(
+            hm.top_at(0, 2),
+            vec![consumer(1, 70.0), consumer(2, 10.0), consumer(3, 5.0)]
+        );
+        drop(hm);
+
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_journal_torn_segment() {
+        let db = test_db_dir("torn");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        hm.add_value_to(&HistogramKind::Mem, 42);
+        hm.save_histograms();
+        hm.db = None;
+        drop(hm);
+        let mut journal = fs::OpenOptions::new()
+            .append(true)
+            .open(db.join("journal"))
+            .unwrap();
+        journal.write_all(&[7, 0, 0, 0, 1, 2]).unwrap();
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        let data = hm.get(&HistogramKind::Mem).unwrap().data();
+        assert_eq!(data[data.len() - 1], 42);
+        // the damaged journal is compacted away on the next save
+        hm.save_histograms();
+        assert!(!db.join("journal").exists());
+        hm.db = None;
+        drop(hm);
+
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_check_journal() {
+        let db = test_db_dir("check-journal");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        for v in [1, 2] {
+            hm.add_value_to(&HistogramKind::Mem, v);
+            hm.save_histograms();
+        }
+        hm.db = None;
+        drop(hm);
+        let journal = db.join("journal");
+        let check = check_journal(&journal).unwrap();
+        assert_eq!((check.tick, check.segments), (Some(tick), 2));
+        assert!(check.damaged.is_none());
+
+        // a whole frame that doesn't hold a segment ends the replay
+        journal::append_frame(&journal, b"not a segment").unwrap();
+        let check = check_journal(&journal).unwrap();
+        assert_eq!(check.segments, 2);
+        assert!(check.damaged.unwrap().starts_with("segment 3"));
+
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_migrates_v1_store() {
+        let db = test_db_dir("v1");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let now = SystemTime::now();
+
+        // zenith 0.15 wrote the gzipped map with no header
+        let mut map = HashMap::new();
+        map.insert(HistogramKind::Cpu, vec![1u64, 2, 3]);
+        let v1 = (map, dur, tick, Some(db.clone()), Some(now));
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(&bincode::serialize(&v1).unwrap()).unwrap();
+        fs::write(db.join("store"), gz.finish().unwrap()).unwrap();
+
+        let mut hm = load_zenith_store(&db.join("store"), &now).unwrap();
+        hm.db = Some(db.clone());
+        assert_eq!(hm.get(&HistogramKind::Cpu).unwrap().data(), &[1, 2, 3]);
+        // the rollup tiers are rebuilt from the raw samples
+        assert_eq!(hm.get(&HistogramKind::Cpu).unwrap().rollups[0].len(), 1);
+        hm.compact();
+        hm.db = None;
+        drop(hm);
+
+        let data = fs::read(db.join("store")).unwrap();
+        assert_eq!(parse_format_header(&data), Some(STORE_VERSION));
+        assert_eq!(
+            read_configuration(&db).get("format"),
+            Some(&STORE_VERSION.to_string())
+        );
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_keeps_newer_store() {
+        let db = test_db_dir("newer");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        let mut store = STORE_MAGIC.to_vec();
+        store.extend_from_slice(&(STORE_VERSION + 1).to_le_bytes());
+        store.extend_from_slice(b"from the future");
+        fs::write(db.join("store"), &store).unwrap();
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert!(!hm.writes_db_store());
+        assert_eq!(hm.newer_store(), Some(STORE_VERSION + 1));
+        hm.add_value_to(&HistogramKind::Cpu, 5);
+        hm.save_histograms();
+        drop(hm);
+
+        assert_eq!(fs::read(db.join("store")).unwrap(), store);
+        assert!(!db.join("journal").exists());
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_keeps_unreadable_store() {
+        let db = test_db_dir("unreadable");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        // reading it fails as it would without permission, it isn't damaged
+        fs::create_dir(db.join("store")).unwrap();
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert!(!hm.writes_db_store());
+        assert!(hm.unreadable_store().is_some());
+        hm.add_value_to(&HistogramKind::Cpu, 5);
+        hm.save_histograms();
+        drop(hm);
+
+        assert!(db.join("store").is_dir());
+        assert_eq!(fs::read_dir(&db).unwrap().count(), 1);
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_keeps_newer_journal() {
+        let db = test_db_dir("newer-journal");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        // nothing compacted yet, the newer zenith only wrote its journal
+        let mut header = STORE_MAGIC.to_vec();
+        header.extend_from_slice(&(STORE_VERSION + 1).to_le_bytes());
+        header.extend(bincode::serialize(&tick).unwrap());
+        journal::append_frame(&db.join("journal"), &header).unwrap();
+        journal::append_frame(&db.join("journal"), b"from the future").unwrap();
+        let journal = fs::read(db.join("journal")).unwrap();
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert!(!hm.writes_db_store());
+        assert_eq!(hm.newer_store(), Some(STORE_VERSION + 1));
+        hm.add_value_to(&HistogramKind::Cpu, 5);
+        hm.save_histograms();
+        drop(hm);
+
+        assert_eq!(fs::read(db.join("journal")).unwrap(), journal);
+        assert!(!db.join("store").exists());
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_store_backups() {
+        let db = test_db_dir("backups");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        for v in 1..=5 {
+            hm.add_value_to(&HistogramKind::Cpu, v);
+            hm.compact();
+        }
+        hm.db = None;
+        drop(hm);
+        for n in 1..=STORE_BACKUPS {
+            assert!(backup_path(&db.join("store"), n).exists());
+        }
+        assert!(!backup_path(&db.join("store"), STORE_BACKUPS + 1).exists());
+
+        let mut store = fs::read(db.join("store")).unwrap();
+        let last = store.len() - 1;
+        store[last] ^= 0xff;
+        fs::write(db.join("store"), &store).unwrap();
+        assert!(matches!(decode_store(&store), Err(StoreError::Corrupt(_))));
+
+        // the newest backup was saved before the last sample
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert!(hm.writes_db_store());
+        let h = hm.get(&HistogramKind::Cpu).unwrap();
+        assert_eq!(h.recorded().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        hm.db = None;
+        drop(hm);
+
+        let aside: Vec<_> = fs::read_dir(&db)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|n| n.starts_with("store.corrupt-"))
+            .collect();
+        assert_eq!(aside.len(), 1);
+        assert_eq!(fs::read(db.join(&aside[0])).unwrap(), store);
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_keeps_other_hosts_store() {
+        let db = test_db_dir("other-host");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert_eq!(hm.host(), Some(hostname().as_str()));
+        hm.host = Some("elsewhere".to_string());
+        hm.add_value_to(&HistogramKind::Cpu, 5);
+        drop(hm);
+        let store = fs::read(db.join("store")).unwrap();
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert!(!hm.writes_db_store());
+        assert_eq!(hm.other_host(), Some("elsewhere"));
+        hm.add_value_to(&HistogramKind::Cpu, 6);
+        drop(hm);
+        assert_eq!(fs::read(db.join("store")).unwrap(), store);
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_viewer() {
+        let db = test_db_dir("viewer");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+
+        let mut recorder = HistogramMap::new(dur, tick, Some(db.clone()));
+        recorder.add_value_to(&HistogramKind::Cpu, 11);
+        recorder.save_histograms();
+
+        // only the journal exists so far
+        let mut viewer = HistogramMap::viewer(dur, tick, db.clone());
+        let data = viewer.get(&HistogramKind::Cpu).unwrap().data();
+        assert_eq!(data[data.len() - 1], 11);
+        viewer.add_value_to(&HistogramKind::Cpu, 99);
+        let data = viewer.get(&HistogramKind::Cpu).unwrap().data();
+        assert_eq!(data[data.len() - 1], 11);
+        assert!(!viewer.reload());
+
+        // make sure the modification time moves on coarse clocks
+        std::thread::sleep(Duration::from_millis(20));
+        recorder.add_value_to(&HistogramKind::Cpu, 12);
+        recorder.compact();
+        assert!(viewer.reload());
+        let data = viewer.get(&HistogramKind::Cpu).unwrap().data();
+        assert_eq!(&data[data.len() - 2..], &[11, 12]);
+        assert_eq!(viewer.viewing(), Some(db.as_path()));
+
+        // the viewer never writes to the database
+        drop(viewer);
+        assert!(!db.join("journal").exists());
+        drop(recorder);
+        fs::remove_dir_all(db).unwrap();
+    }
+
     #[test]
     fn test_histogram_kind_gpu() {
         let gpu_use = HistogramKind::GpuUse("GPU0".to_string());
@@ -490,6 +2757,297 @@ mod tests {
         assert_eq!(gpu_use, HistogramKind::GpuUse("GPU0".to_string()));
     }
 
+    #[test]
+    fn test_histogram_map_gaps() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let mut hm = HistogramMap::new(dur, tick, None);
+
+        hm.add_value_to(&HistogramKind::Cpu, 40);
+        hm.add_value_to(&HistogramKind::Cpu, 60);
+        // zenith wasn't running for 3 ticks
+        hm.previous_stop = Some(SystemTime::now());
+        hm.fill_until(&(hm.previous_stop.unwrap() + tick * 3));
+        hm.add_value_to(&HistogramKind::Cpu, 80);
+
+        let h = hm.get(&HistogramKind::Cpu).unwrap();
+        let points: Vec<_> = h.points().collect();
+        assert_eq!(points[0], None);
+        assert_eq!(
+            &points[points.len() - 6..],
+            &[Some(40), Some(60), None, None, None, Some(80)]
+        );
+        assert_eq!(h.recorded().collect::<Vec<_>>(), vec![40, 60, 80]);
+
+        // averaging skips the gaps, columns with only gaps stay gaps
+        let view = View {
+            zoom_factor: 2,
+            update_number: 0,
+            width: 3,
+            offset: 0,
+            cursor: None,
+        };
+        let zoomed = hm.get_zoomed(&HistogramKind::Cpu, &view).unwrap();
+        assert_eq!(zoomed.data(), &[50, NO_DATA, 80]);
+
+        // the rollups don't count gaps either
+        let pending = &h.rollups[0].pending;
+        assert_eq!(pending.count, 3);
+        assert_eq!(pending.avg(), 60);
+        assert_eq!(Bucket::default().avg(), NO_DATA);
+    }
+
+    #[test]
+    fn test_histogram_map_resample() {
+        let db = test_db_dir("resample");
+        let dur = Duration::from_secs(60);
+
+        let mut hm = HistogramMap::new(dur, Duration::from_secs(2), Some(db.clone()));
+        for v in [10, 20, 30] {
+            hm.add_value_to(&HistogramKind::Cpu, v);
+            hm.add_value_to(&HistogramKind::NetRx, v * 100);
+        }
+        drop(hm);
+
+        // each 2s sample covers two 1s ticks
+        let hm = HistogramMap::new(dur, Duration::from_secs(1), Some(db.clone()));
+        assert_eq!(hm.tick, Duration::from_secs(1));
+        let cpu = hm.get(&HistogramKind::Cpu).unwrap().data();
+        assert_eq!(cpu.len(), 60);
+        assert_eq!(&cpu[cpu.len() - 6..], &[10, 10, 20, 20, 30, 30]);
+        let net = hm.get(&HistogramKind::NetRx).unwrap().data();
+        assert_eq!(&net[net.len() - 6..], &[500, 500, 1000, 1000, 1500, 1500]);
+        drop(hm);
+
+        // and back, pairs of 1s ticks are combined again
+        let hm = HistogramMap::new(dur, Duration::from_secs(2), Some(db.clone()));
+        let cpu = hm.get(&HistogramKind::Cpu).unwrap().data();
+        assert_eq!(cpu.len(), 30);
+        assert_eq!(&cpu[cpu.len() - 3..], &[10, 20, 30]);
+        let net = hm.get(&HistogramKind::NetRx).unwrap().data();
+        assert_eq!(&net[net.len() - 3..], &[1000, 2000, 3000]);
+        drop(hm);
+
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_resample_rollups() {
+        let tiers = ROLLUP_TIERS.to_vec();
+        let tick = Duration::from_secs(2);
+        let mut h = Histogram::new(0, tiers.len());
+        // a minute and a half of 2s ticks moving 1000 bytes each
+        h.push(1000, 45, 100, &tiers, tick);
+        h.resample(tick, Duration::from_secs(1), 100, true);
+
+        // the same rate in 1s ticks
+        let minutes = &h.rollups[0];
+        let closed = minutes.range(0, 1)[0];
+        assert_eq!((closed.min, closed.max, closed.avg()), (500, 500, 500));
+        assert_eq!(minutes.pending.avg(), 500);
+        assert_eq!(minutes.pending_ticks, 30);
+    }
+
+    #[test]
+    fn test_histogram_map_sample_times() {
+        let db = test_db_dir("times");
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        // times are kept to the millisecond
+        let t0 = from_epoch_millis(epoch_millis(SystemTime::now()) - 10_000);
+
+        let mut hm = HistogramMap::new(dur, tick, Some(db.clone()));
+        assert_eq!(hm.time_at(0), None);
+        // capture times don't have to be exactly one tick apart
+        for (i, ms) in [0, 1100, 1900].iter().enumerate() {
+            hm.begin_tick(t0 + Duration::from_millis(*ms));
+            hm.add_value_to(&HistogramKind::Cpu, i as u64);
+        }
+        assert_eq!(hm.time_at(0), Some(t0 + Duration::from_millis(1900)));
+        assert_eq!(hm.time_at(2), Some(t0));
+        // older ticks are estimated
+        assert_eq!(hm.time_at(4), Some(t0 - tick * 2));
+        hm.save_histograms();
+        hm.db = None;
+        drop(hm);
+
+        // the times survive the journal
+        let hm = HistogramMap::viewer(dur, tick, db.clone());
+        assert_eq!(hm.time_at(1), Some(t0 + Duration::from_millis(1100)));
+        let samples = hm.timestamped(&HistogramKind::Cpu);
+        assert_eq!(samples[2], (t0 + Duration::from_millis(1900), 2));
+        drop(hm);
+
+        fs::remove_dir_all(db).unwrap();
+    }
+
+    #[test]
+    fn test_histogram_map_retention() {
+        let tick = Duration::from_secs(1);
+        let mut hm = HistogramMap::new(Duration::from_secs(60 * 60), tick, None);
+        assert_eq!(hm.recorded_span(), None);
+        for v in 0..(60 * 60 * 3) {
+            hm.add_value_to(&HistogramKind::Cpu, v % 100);
+        }
+        // the minute tier covers the 3 hours recorded so far
+        assert_eq!(hm.recorded_span(), Some(Duration::from_secs(60 * 60 * 3)));
+
+        hm.set_retention(Retention {
+            memory_window: Duration::from_secs(60 * 60),
+            history: Duration::from_secs(60 * 60 * 2),
+        });
+        assert_eq!(hm.raw_max(), 60 * 60);
+        assert_eq!(hm.tiers[0].retention, Duration::from_secs(60 * 60 * 2));
+        assert_eq!(hm.tiers[1].retention, Duration::from_secs(60 * 60 * 2));
+        let h = hm.get(&HistogramKind::Cpu).unwrap();
+        assert_eq!(h.rollups[0].buckets.len(), 120);
+        assert_eq!(hm.recorded_span(), Some(Duration::from_secs(60 * 60 * 2)));
+
+        // the memory window never exceeds the retention
+        hm.set_retention(Retention {
+            memory_window: Duration::from_secs(60 * 60 * 24),
+            history: Duration::from_secs(60 * 30),
+        });
+        assert_eq!(hm.raw_max(), 60 * 30);
+        assert_eq!(hm.get(&HistogramKind::Cpu).unwrap().data().len(), 60 * 30);
+    }
+
+    #[test]
+    fn test_histogram_map_window_view() {
+        let dur = Duration::from_secs(60 * 60);
+        let tick = Duration::from_secs(2);
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let mut hm = HistogramMap::new(dur, tick, None);
+        assert!(hm.window_view(t0, t0 + tick, 100).is_none());
+        for i in 0..1000u32 {
+            hm.begin_tick(t0 + tick * i);
+            hm.add_value_to(&HistogramKind::Cpu, 10);
+        }
+        let newest = t0 + tick * 999;
+        let minutes = |m: u64| Duration::from_secs(60 * m);
+
+        // the last 5 minutes, 150 ticks, over 100 columns
+        assert_eq!(
+            hm.window_view(newest - minutes(5), newest, 100),
+            Some((2, 0))
+        );
+        // ten minutes ending 20 minutes ago
+        let (zoom, offset) = hm
+            .window_view(newest - minutes(30), newest - minutes(20), 100)
+            .unwrap();
+        assert_eq!((zoom, offset), (3, 200));
+        let view = View {
+            zoom_factor: zoom,
+            update_number: 0,
+            width: 100,
+            offset,
+            cursor: None,
+        };
+        // its 300 ticks fill the 100 columns
+        assert_eq!(hm.column_age(&view, 0), 600);
+        assert_eq!(hm.column_age(&view, 99) + zoom as usize - 1, 899);
+
+        // short windows aren't zoomed in further than a tick per column
+        assert_eq!(hm.window_view(newest - tick, newest, 100), Some((1, 0)));
+        // nor out beyond the longest tier
+        let (zoom, _) = hm
+            .window_view(newest - minutes(60 * 24 * 365 * 2), newest, 100)
+            .unwrap();
+        assert_eq!(zoom, hm.max_zoom_factor(100));
+        assert_eq!(
+            hm.window_view(newest + minutes(1), newest + minutes(2), 100),
+            Some((1, 0))
+        );
+    }
+
+    #[test]
+    fn test_histogram_map_prune_before() {
+        let dur = Duration::from_secs(60 * 60);
+        let tick = Duration::from_secs(60);
+        let t0 = from_epoch_millis(epoch_millis(SystemTime::now()) - 60 * 60 * 1000);
+        let mut hm = HistogramMap::new(dur, tick, None);
+        for i in 0..=60u32 {
+            hm.begin_tick(t0 + tick * i);
+            hm.add_value_to(&HistogramKind::Cpu, 10);
+            hm.add_value_to(&HistogramKind::Mem, 20);
+        }
+        hm.prune_before(t0 + tick * 50);
+        let h = hm.get(&HistogramKind::Cpu).unwrap();
+        assert_eq!(h.recorded().count(), 11);
+        // the raw series keeps its length with gaps in front
+        assert_eq!(h.data().len(), 61);
+        // minute buckets go with the samples, the hour still being filled stays
+        assert_eq!(h.recorded_buckets(0), 11);
+        assert_eq!(h.recorded_buckets(1), 1);
+        assert_eq!(hm.time_at(10), Some(t0 + tick * 50));
+
+        assert!(hm.drop_series(&HistogramKind::Mem));
+        assert!(!hm.drop_series(&HistogramKind::Mem));
+        assert_eq!(hm.kinds().count(), 1);
+    }
+
+    #[test]
+    fn test_histogram_kind_names() {
+        for k in [
+            HistogramKind::Cpu,
+            HistogramKind::NetRx,
+            HistogramKind::IoRead("/dev/sda".to_string()),
+            HistogramKind::FileSystemUsedSpace("/mnt:data".to_string()),
+            HistogramKind::NetIfaceRx("eth0".to_string()),
+            HistogramKind::NetIfacePacketsTx("wlan0".to_string()),
+            HistogramKind::NetIfaceDropsRx("eth0".to_string()),
+            HistogramKind::TcpListenOverflows,
+        ] {
+            assert_eq!(k.to_string().parse::<HistogramKind>(), Ok(k));
+        }
+        assert!("io_read".parse::<HistogramKind>().is_err());
+        assert!("cpu:0".parse::<HistogramKind>().is_err());
+    }
+
+    #[test]
+    fn test_histogram_map_timestamped() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(2);
+        let mut hm = HistogramMap::new(dur, tick, None);
+        assert!(hm.timestamped(&HistogramKind::Cpu).is_empty());
+
+        let stop = SystemTime::now();
+        hm.add_value_to(&HistogramKind::Cpu, 7);
+        hm.add_value_to(&HistogramKind::Cpu, 9);
+        hm.previous_stop = Some(stop);
+        let samples = hm.timestamped(&HistogramKind::Cpu);
+        // the prefilled gaps aren't samples
+        assert_eq!(samples, vec![(stop - tick, 7), (stop, 9)]);
+    }
+
+    #[test]
+    fn test_histogram_map_timestamped_rollups() {
+        let dur = Duration::from_secs(60);
+        let tick = Duration::from_secs(1);
+        let t0 = from_epoch_millis(epoch_millis(SystemTime::now()) - 1_000_000);
+        let mut hm = HistogramMap::new(dur, tick, None);
+        // five minutes, each at its own value, and the start of a sixth
+        for i in 0..330u32 {
+            hm.begin_tick(t0 + tick * i);
+            hm.add_value_to(&HistogramKind::Cpu, (i / 60) as u64);
+        }
+        let samples = hm.timestamped(&HistogramKind::Cpu);
+        // the raw samples keep the last minute, the minutes before come from the rollup
+        let raw: Vec<_> = (300..330).map(|i| (t0 + tick * i, 5)).collect();
+        assert_eq!(&samples[samples.len() - 30..], &raw[..]);
+        assert_eq!(
+            &samples[..samples.len() - 30],
+            &[
+                (t0 + tick * 59, 0),
+                (t0 + tick * 119, 1),
+                (t0 + tick * 179, 2),
+                (t0 + tick * 239, 3),
+                // reaches past the raw samples, so it is shown in place of those it covers
+                (t0 + tick * 299, 4),
+            ]
+        );
+    }
+
     #[test]
     fn test_histogram_kind_file_system() {
         let fs1 = HistogramKind::FileSystemUsedSpace("/dev/sda1".to_string());
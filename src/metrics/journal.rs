@@ -0,0 +1,164 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Append-only file of length and checksum prefixed frames.
+//!
+//! Each frame is written as `[len: u32 LE][crc32: u32 LE][payload]`. Readers stop at the
+//! first frame that is short or fails its checksum, so a write torn by a crash only loses
+//! the frame that was being written.
+
+use flate2::Crc;
+use std::convert::{TryFrom, TryInto};
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const FRAME_HEADER_LEN: usize = 8;
+
+pub fn checksum(payload: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(payload);
+    crc.sum()
+}
+
+/// Writes one frame to `w`.
+pub fn write_frame<W: Write>(w: &mut W, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large"))?;
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.extend_from_slice(&checksum(payload).to_le_bytes());
+    frame.extend_from_slice(payload);
+    w.write_all(&frame)
+}
+
+/// Appends one frame to the file at `path` and syncs it to disk.
+pub fn append_frame(path: &Path, payload: &[u8]) -> io::Result<()> {
+    let mut f = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    write_frame(&mut f, payload)?;
+    f.sync_data()
+}
+
+pub struct Frames {
+    pub frames: Vec<Vec<u8>>,
+    /// Bytes after the last intact frame, non zero when the last write was torn.
+    pub torn_bytes: usize,
+}
+
+/// Splits `data` into frames, stopping at the first damaged one.
+pub fn parse_frames(data: &[u8]) -> Frames {
+    let mut frames = vec![];
+    let mut pos = 0;
+    while data.len() - pos >= FRAME_HEADER_LEN {
+        let len = u32::from_le_bytes(data[pos..pos + 4].try_into().expect("4 bytes")) as usize;
+        let crc = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().expect("4 bytes"));
+        let start = pos + FRAME_HEADER_LEN;
+        if data.len() - start < len {
+            break;
+        }
+        let payload = &data[start..start + len];
+        if checksum(payload) != crc {
+            break;
+        }
+        frames.push(payload.to_vec());
+        pos = start + len;
+    }
+    Frames {
+        frames,
+        torn_bytes: data.len() - pos,
+    }
+}
+
+/// Reads the next frame from `r`. The end of the input, a short frame and one that fails its
+/// checksum all end the frames.
+pub fn read_frame<R: Read>(r: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut header = [0u8; FRAME_HEADER_LEN];
+    match r.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_le_bytes(header[..4].try_into().expect("4 bytes")) as usize;
+    let crc = u32::from_le_bytes(header[4..].try_into().expect("4 bytes"));
+    let mut payload = vec![];
+    r.take(len as u64).read_to_end(&mut payload)?;
+    if payload.len() < len || checksum(&payload) != crc {
+        return Ok(None);
+    }
+    Ok(Some(payload))
+}
+
+/// Reads all intact frames of the file at `path`. A missing file has no frames.
+pub fn read_frames(path: &Path) -> io::Result<Frames> {
+    match fs::read(path) {
+        Ok(data) => Ok(parse_frames(&data)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Frames {
+            frames: vec![],
+            torn_bytes: 0,
+        }),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_round_trip() {
+        let mut buf = vec![];
+        write_frame(&mut buf, b"first").unwrap();
+        write_frame(&mut buf, b"").unwrap();
+        write_frame(&mut buf, b"third").unwrap();
+
+        let frames = parse_frames(&buf);
+        assert_eq!(
+            frames.frames,
+            vec![b"first".to_vec(), vec![], b"third".to_vec()]
+        );
+        assert_eq!(frames.torn_bytes, 0);
+    }
+
+    #[test]
+    fn test_frames_torn_write() {
+        let mut buf = vec![];
+        write_frame(&mut buf, b"first").unwrap();
+        write_frame(&mut buf, b"second").unwrap();
+        buf.truncate(buf.len() - 3);
+
+        let frames = parse_frames(&buf);
+        assert_eq!(frames.frames, vec![b"first".to_vec()]);
+        assert_eq!(frames.torn_bytes, FRAME_HEADER_LEN + 3);
+    }
+
+    #[test]
+    fn test_read_frame() {
+        let mut buf = vec![];
+        write_frame(&mut buf, b"first").unwrap();
+        write_frame(&mut buf, b"second").unwrap();
+        buf.truncate(buf.len() - 1);
+
+        let mut r = buf.as_slice();
+        assert_eq!(read_frame(&mut r).unwrap(), Some(b"first".to_vec()));
+        assert_eq!(read_frame(&mut r).unwrap(), None);
+        assert_eq!(read_frame(&mut &[][..]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_frames_corrupt_payload() {
+        let mut buf = vec![];
+        write_frame(&mut buf, b"first").unwrap();
+        write_frame(&mut buf, b"second").unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 0xff;
+
+        let frames = parse_frames(&buf);
+        assert_eq!(frames.frames.len(), 1);
+        assert!(frames.torn_bytes > 0);
+    }
+}
@@ -4,6 +4,14 @@
 pub mod disk;
 pub mod graphics;
 pub mod histogram;
+pub mod journal;
+pub mod netstat;
+pub mod network;
+pub mod samples;
+pub mod session;
+pub mod snapshots;
+pub mod sockets;
+pub mod top;
 pub mod zprocess;
 
 #[cfg(target_os = "macos")]
@@ -11,9 +19,14 @@ pub mod memory_mac;
 
 use crate::metrics::disk::{get_device_name, get_disk_io_metrics, IoMetrics, ZDisk};
 use crate::metrics::graphics::device::{GraphicsDevice, GraphicsExt};
-use crate::metrics::histogram::{HistogramKind, HistogramMap};
+use crate::metrics::histogram::{HistogramKind, HistogramMap, Retention};
 #[cfg(target_os = "macos")]
 use crate::metrics::memory_mac::get_macos_memory_used;
+use crate::metrics::netstat::{NetCounters, TcpCounters};
+use crate::metrics::network::{InterfaceTraffic, NetTotal};
+use crate::metrics::session::{Host, Tick};
+use crate::metrics::sockets::{ListeningPort, Socket, SocketTraffic};
+use crate::metrics::top::{Consumer, TopMetric, TOP_CONSUMERS};
 use crate::metrics::zprocess::set_addl_task_info;
 use crate::metrics::zprocess::ZProcess;
 use crate::util::percent_of;
@@ -24,6 +37,7 @@ use heim::net;
 use heim::net::Address;
 use heim::units::frequency::megahertz;
 use heim::units::time;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::time::{Duration, SystemTime};
 
@@ -37,7 +51,7 @@ use nvml::{cuda_driver_version_major, cuda_driver_version_minor};
 
 use std::fs;
 use std::path::{Path, PathBuf};
-use sysinfo::{Component, Components, Disk, Disks, Networks, System};
+use sysinfo::{Component, Components, Disk, Disks, Networks, System, ThreadKind};
 use uzers::{Users, UsersCache};
 
 #[cfg(all(feature = "nvidia", not(target_os = "linux")))]
@@ -74,9 +88,11 @@ pub enum ProcessTableSortBy {
     DiskRead = 9,
     DiskWrite = 10,
     IOWait = 11,
-    Gpu = 12,
-    FB = 13,
-    Cmd = 14,
+    NetRx = 12,
+    NetTx = 13,
+    Gpu = 14,
+    FB = 15,
+    Cmd = 16,
 }
 
 #[cfg(all(not(feature = "nvidia"), not(target_os = "linux")))]
@@ -111,10 +127,12 @@ pub enum ProcessTableSortBy {
     DiskRead = 9,
     DiskWrite = 10,
     IOWait = 11,
-    Cmd = 12,
+    NetRx = 12,
+    NetTx = 13,
+    Cmd = 14,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 pub enum ProcessTableSortOrder {
     Ascending = 0,
     Descending = 1,
@@ -134,12 +152,14 @@ impl DiskFreeSpaceExt for Disk {
     }
 }
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NetworkInterface {
     pub name: String,
     pub ip: String,
     pub dest: String,
 }
 #[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Sensor {
     pub name: String,
     pub current_temp: f32,
@@ -202,6 +222,10 @@ pub struct Top {
     pub write: ValAndPid<f64>,
     #[cfg(target_os = "linux")]
     pub iowait: ValAndPid<f64>,
+    #[cfg(target_os = "linux")]
+    pub net_rx: ValAndPid<f64>,
+    #[cfg(target_os = "linux")]
+    pub net_tx: ValAndPid<f64>,
     #[cfg(all(target_os = "linux", feature = "nvidia"))]
     pub gpu: ValAndPid<u64>,
     #[cfg(all(target_os = "linux", feature = "nvidia"))]
@@ -217,6 +241,12 @@ impl Top {
         self.write.update(zp.get_write_bytes_sec(tick_rate), zp.pid);
         #[cfg(target_os = "linux")]
         self.iowait.update(zp.get_io_wait(tick_rate), zp.pid);
+        #[cfg(target_os = "linux")]
+        self.net_rx
+            .update(zp.get_net_rx_bytes_sec(tick_rate), zp.pid);
+        #[cfg(target_os = "linux")]
+        self.net_tx
+            .update(zp.get_net_tx_bytes_sec(tick_rate), zp.pid);
         #[cfg(all(target_os = "linux", feature = "nvidia"))]
         self.gpu.update(zp.gpu_usage, zp.pid);
         #[cfg(all(target_os = "linux", feature = "nvidia"))]
@@ -224,6 +254,27 @@ impl Top {
     }
 }
 
+/// Orders `pids` by a column of their processes in `pm`.
+pub(crate) fn sort_pids(
+    pids: &mut [u32],
+    pm: &HashMap<u32, ZProcess>,
+    sortby: ProcessTableSortBy,
+    sortorder: ProcessTableSortOrder,
+    tick: &Duration,
+) {
+    let sorter = ZProcess::field_comparator(sortby);
+    pids.sort_by(|a, b| {
+        let pa = pm.get(a).expect("Error in sorting the process table.");
+        let pb = pm.get(b).expect("Error in sorting the process table.");
+
+        let ord = sorter(pa, pb, tick);
+        match sortorder {
+            ProcessTableSortOrder::Ascending => ord,
+            ProcessTableSortOrder::Descending => ord.reverse(),
+        }
+    });
+}
+
 #[allow(dead_code)]
 pub struct CPUTimeApp {
     pub histogram_map: HistogramMap,
@@ -242,6 +293,14 @@ pub struct CPUTimeApp {
     pub system: System,
     pub net_in: u64,
     pub net_out: u64,
+    /// Traffic of each network interface, by name.
+    pub interfaces: Vec<InterfaceTraffic>,
+    /// Interfaces counted in `net_in` and `net_out`.
+    pub net_total: NetTotal,
+    net_counters: NetCounters,
+    socket_traffic: SocketTraffic,
+    /// TCP counters during the last tick, where the system has them.
+    pub tcp: Option<TcpCounters>,
     pub processes: Vec<u32>,
     pub process_map: HashMap<u32, ZProcess>,
     pub user_cache: UsersCache,
@@ -262,6 +321,10 @@ pub struct CPUTimeApp {
     pub processor_name: String,
     pub started: chrono::DateTime<chrono::Local>,
     pub selected_process: Option<Box<ZProcess>>,
+    /// Sockets of the selected process, or why they can't be listed.
+    pub process_sockets: Result<Vec<Socket>, String>,
+    /// Listening TCP and UDP sockets by port, read while they are shown.
+    pub listening_ports: Vec<ListeningPort>,
     pub max_pid_len: usize,
     pub batteries: Vec<starship_battery::Battery>,
     pub uptime: Duration,
@@ -280,9 +343,22 @@ pub struct CPUTimeApp {
 }
 
 impl CPUTimeApp {
-    pub fn new(tick: Duration, db: Option<PathBuf>) -> CPUTimeApp {
+    pub fn new(
+        tick: Duration,
+        db: Option<PathBuf>,
+        view: bool,
+        retention: Retention,
+    ) -> CPUTimeApp {
         debug!("Create Histogram Map");
-        let histogram_map = HistogramMap::new(Duration::from_secs(60 * 60 * 24), tick, db);
+        let dur = retention.memory_window;
+        let histogram_map = match db {
+            Some(db) if view => HistogramMap::viewer(dur, tick, db),
+            db => {
+                let mut hm = HistogramMap::new(dur, tick, db);
+                hm.set_retention(retention);
+                hm
+            }
+        };
         #[cfg(all(target_os = "linux", feature = "nvidia"))]
         let mut ne = None;
         #[cfg(all(target_os = "linux", feature = "nvidia"))]
@@ -332,6 +408,11 @@ impl CPUTimeApp {
             disks: HashMap::with_capacity(10),
             net_in: 0,
             net_out: 0,
+            interfaces: vec![],
+            net_total: NetTotal::default(),
+            net_counters: NetCounters::default(),
+            socket_traffic: SocketTraffic::default(),
+            tcp: None,
             processes: Vec::with_capacity(400),
             process_map: HashMap::with_capacity(400),
             user_cache: UsersCache::new(),
@@ -352,6 +433,8 @@ impl CPUTimeApp {
             processor_name: String::from(""),
             started: chrono::Local::now(),
             selected_process: None,
+            process_sockets: Ok(vec![]),
+            listening_ports: vec![],
             max_pid_len: get_max_pid_length(),
             batteries: vec![],
             top_pids: Top::default(),
@@ -487,18 +570,55 @@ impl CPUTimeApp {
         self.selected_process = highlighted_process;
     }
 
+    /// Lists the listening sockets again with the processes holding them, threads left out as
+    /// they share the sockets of their process.
+    pub fn update_listening_ports(&mut self) {
+        #[cfg(target_os = "linux")]
+        {
+            let pids = self
+                .system
+                .processes()
+                .values()
+                .filter(|p| p.thread_kind() != Some(ThreadKind::Userland))
+                .map(|p| p.pid().as_u32());
+            self.listening_ports = sockets::read_listening_ports(pids);
+        }
+    }
+
+    /// Lists the sockets of the selected process again, those it had are kept once it ended.
+    pub fn update_process_sockets(&mut self) {
+        let Some(p) = &self.selected_process else {
+            self.process_sockets = Ok(vec![]);
+            return;
+        };
+        if p.end_time.is_some() {
+            return;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            self.process_sockets = sockets::process_sockets(p.pid).map_err(|e| e.to_string());
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            self.process_sockets = Err(String::from("Sockets are only listed on Linux."));
+        }
+    }
+
     fn update_process_list(&mut self, keep_order: bool) {
         debug!("Updating Process List");
         let process_list = self.system.processes();
         #[cfg(target_os = "linux")]
         let client = &self.netlink_client;
         let mut current_pids: HashSet<u32> = HashSet::with_capacity(process_list.len());
+        // threads share the sockets of their process
+        let net = self.socket_traffic.read(
+            process_list
+                .values()
+                .filter(|p| p.thread_kind() != Some(ThreadKind::Userland))
+                .map(|p| p.pid().as_u32()),
+        );
 
-        let mut top = Top::default();
-        top.cum_cpu.val = match &self.cum_cpu_process {
-            Some(p) => p.cum_cpu_usage,
-            None => 0.0,
-        };
+        let mut top = self.new_top();
 
         self.threads_total = 0;
 
@@ -520,6 +640,9 @@ impl CPUTimeApp {
                     zp.last_updated = SystemTime::now();
                     #[cfg(target_os = "linux")]
                     zp.update_delay(client);
+                    let bytes = net.get(&zp.pid).copied().unwrap_or_default();
+                    zp.net_rx_bytes = bytes.rx;
+                    zp.net_tx_bytes = bytes.tx;
 
                     set_addl_task_info(zp);
 
@@ -530,6 +653,10 @@ impl CPUTimeApp {
                     let write_rate = zp.get_write_bytes_sec(&self.histogram_map.tick);
                     zp.peak_read_bytes_sec = zp.peak_read_bytes_sec.max(read_rate);
                     zp.peak_write_bytes_sec = zp.peak_write_bytes_sec.max(write_rate);
+                    let net_rx_rate = zp.get_net_rx_bytes_sec(&self.histogram_map.tick);
+                    let net_tx_rate = zp.get_net_tx_bytes_sec(&self.histogram_map.tick);
+                    zp.peak_net_rx_bytes_sec = zp.peak_net_rx_bytes_sec.max(net_rx_rate);
+                    zp.peak_net_tx_bytes_sec = zp.peak_net_tx_bytes_sec.max(net_tx_rate);
                     zp.peak_gpu_usage = zp.peak_gpu_usage.max(zp.gpu_usage);
                     zp.peak_fb_utilization = zp.peak_fb_utilization.max(zp.fb_utilization);
 
@@ -569,6 +696,21 @@ impl CPUTimeApp {
             current_pids.insert(pid.as_u32());
         }
 
+        self.finish_process_list(top, &current_pids, keep_order);
+    }
+
+    /// Top processes to be updated with this tick's, keeping the cumulative CPU leader.
+    fn new_top(&self) -> Top {
+        let mut top = Top::default();
+        top.cum_cpu.val = match &self.cum_cpu_process {
+            Some(p) => p.cum_cpu_usage,
+            None => 0.0,
+        };
+        top
+    }
+
+    /// Drops the processes that are gone and updates the ones picked out of the table.
+    fn finish_process_list(&mut self, top: Top, current_pids: &HashSet<u32>, keep_order: bool) {
         if keep_order {
             self.processes.retain(|pid| current_pids.contains(pid));
         } else {
@@ -613,22 +755,53 @@ impl CPUTimeApp {
         }
     }
 
+    /// Keeps the processes using the most CPU, memory, disk and network this tick with the
+    /// history.
+    fn record_top_consumers(&mut self) {
+        let tick = self.histogram_map.tick;
+        type Value = fn(&ZProcess, &Duration) -> f64;
+        #[allow(unused_mut)]
+        let mut metrics: Vec<(TopMetric, Value)> = vec![
+            (TopMetric::Cpu, |p, _| p.cpu_usage as f64),
+            (TopMetric::Mem, |p, _| p.memory as f64),
+            (TopMetric::Read, |p, t| p.get_read_bytes_sec(t)),
+            (TopMetric::Write, |p, t| p.get_write_bytes_sec(t)),
+        ];
+        #[cfg(target_os = "linux")]
+        metrics.extend([
+            (TopMetric::IoWait, (|p, t| p.get_io_wait(t)) as Value),
+            (TopMetric::NetRx, |p, t| p.get_net_rx_bytes_sec(t)),
+            (TopMetric::NetTx, |p, t| p.get_net_tx_bytes_sec(t)),
+        ]);
+
+        let mut consumers = vec![];
+        for (metric, value) in metrics {
+            let mut top: Vec<(f64, &ZProcess)> = self
+                .process_map
+                .values()
+                .map(|p| (value(p, &tick), p))
+                .filter(|(v, _)| *v > 0.0)
+                .collect();
+            top.sort_by(|a, b| b.0.total_cmp(&a.0));
+            consumers.extend(top.into_iter().take(TOP_CONSUMERS).map(|(v, p)| Consumer {
+                metric,
+                pid: p.pid,
+                name: p.name.clone(),
+                value: v as f32,
+            }));
+        }
+        self.histogram_map.record_top(consumers);
+    }
+
     pub fn sort_process_table(&mut self) {
         debug!("Sorting Process Table");
-        let pm = &self.process_map;
-        let sorter = ZProcess::field_comparator(self.psortby);
-        let sortorder = &self.psortorder;
-        let tick = self.histogram_map.tick;
-        self.processes.sort_by(|a, b| {
-            let pa = pm.get(a).expect("Error in sorting the process table.");
-            let pb = pm.get(b).expect("Error in sorting the process table.");
-
-            let ord = sorter(pa, pb, &tick);
-            match sortorder {
-                ProcessTableSortOrder::Ascending => ord,
-                ProcessTableSortOrder::Descending => ord.reverse(),
-            }
-        });
+        sort_pids(
+            &mut self.processes,
+            &self.process_map,
+            self.psortby,
+            self.psortorder,
+            &self.histogram_map.tick,
+        );
     }
 
     async fn update_frequency(&mut self) {
@@ -806,24 +979,96 @@ impl CPUTimeApp {
     }
 
     pub async fn update_networks(&mut self) {
-        let mut net_in = 0;
-        let mut net_out = 0;
-        self.networks.refresh(false);
-        for (_iface, data) in self.networks.iter() {
-            debug!("iface: {}", _iface);
-            net_in += data.received();
-            net_out += data.transmitted();
-        }
-        self.net_in = net_in;
-        self.net_out = net_out;
+        // interfaces that went away, e.g. the veth of a stopped container, are no longer listed,
+        // their series get gaps until the history drops them
+        self.networks.refresh(true);
+        let (errors, tcp) = self.net_counters.read();
+        self.interfaces = self
+            .networks
+            .iter()
+            .map(|(iface, data)| {
+                let mut traffic = InterfaceTraffic::new(
+                    iface,
+                    data.received(),
+                    data.transmitted(),
+                    data.packets_received(),
+                    data.packets_transmitted(),
+                );
+                match errors.get(iface) {
+                    Some(e) => {
+                        traffic.rx_errors = e.rx_errors;
+                        traffic.tx_errors = e.tx_errors;
+                        traffic.rx_drops = e.rx_drops;
+                        traffic.tx_drops = e.tx_drops;
+                    }
+                    // no drop counters without /proc/net/dev
+                    None => {
+                        traffic.rx_errors = data.errors_on_received();
+                        traffic.tx_errors = data.errors_on_transmitted();
+                    }
+                }
+                traffic
+            })
+            .collect();
+        self.interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+        let total = self.net_total.sum(&self.interfaces);
+        self.net_in = total.rx_bytes;
+        self.net_out = total.tx_bytes;
+        self.tcp = tcp;
+        self.add_network_values();
+    }
+
+    fn add_network_values(&mut self) {
         self.histogram_map
             .add_value_to(&HistogramKind::NetRx, self.net_in);
         self.histogram_map
             .add_value_to(&HistogramKind::NetTx, self.net_out);
+        for iface in self.interfaces.iter() {
+            let name = &iface.name;
+            self.histogram_map
+                .add_value_to(&HistogramKind::NetIfaceRx(name.clone()), iface.rx_bytes);
+            self.histogram_map
+                .add_value_to(&HistogramKind::NetIfaceTx(name.clone()), iface.tx_bytes);
+            self.histogram_map.add_value_to(
+                &HistogramKind::NetIfacePacketsRx(name.clone()),
+                iface.rx_packets,
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::NetIfacePacketsTx(name.clone()),
+                iface.tx_packets,
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::NetIfaceErrorsRx(name.clone()),
+                iface.rx_errors,
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::NetIfaceErrorsTx(name.clone()),
+                iface.tx_errors,
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::NetIfaceDropsRx(name.clone()),
+                iface.rx_drops,
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::NetIfaceDropsTx(name.clone()),
+                iface.tx_drops,
+            );
+        }
+        let present: HashSet<&str> = self.interfaces.iter().map(|i| i.name.as_str()).collect();
+        self.histogram_map.add_gone_interfaces(&present);
+        if let Some(tcp) = self.tcp {
+            self.histogram_map
+                .add_value_to(&HistogramKind::TcpRetransmits, tcp.retransmits);
+            self.histogram_map
+                .add_value_to(&HistogramKind::TcpResets, tcp.resets);
+            self.histogram_map
+                .add_value_to(&HistogramKind::TcpListenOverflows, tcp.listen_overflows);
+        }
     }
 
     pub async fn update(&mut self, keep_order: bool) {
         debug!("Updating Metrics");
+        self.histogram_map.begin_tick(SystemTime::now());
         self.system.refresh_all();
         self.update_cpu().await;
         self.update_sensors().await;
@@ -847,6 +1092,8 @@ impl CPUTimeApp {
 
         self.update_networks().await;
         self.update_process_list(keep_order);
+        self.update_process_sockets();
+        self.record_top_consumers();
         self.update_frequency().await;
         self.update_disk().await;
         self.get_platform().await;
@@ -858,6 +1105,126 @@ impl CPUTimeApp {
         debug!("Updated Metrics for {} processes.", self.processes.len());
     }
 
+    /// Takes the host details of a recording that is replayed.
+    pub fn replay_host(&mut self, host: &Host) {
+        self.hostname = host.hostname.clone();
+        self.osname = host.osname.clone();
+        self.release = host.release.clone();
+        self.version = host.version.clone();
+        self.arch = host.arch.clone();
+        self.processor_name = host.processor_name.clone();
+    }
+
+    /// Shows a tick of a recording as if it had just been sampled.
+    pub fn replay(&mut self, tick: Tick, keep_order: bool) {
+        debug!("Replaying Metrics");
+        self.histogram_map.begin_tick(tick.time);
+        self.cpu_utilization = tick.cpu_utilization;
+        self.cpus = tick.cpus;
+        self.frequency = tick.frequency;
+        self.histogram_map
+            .add_value_to(&HistogramKind::Cpu, self.cpu_utilization);
+        self.sensors = tick.sensors;
+
+        self.mem_utilization = tick.mem_utilization;
+        self.mem_total = tick.mem_total;
+        let mem = percent_of(self.mem_utilization, self.mem_total) as u64;
+        self.histogram_map.add_value_to(&HistogramKind::Mem, mem);
+        self.swap_utilization = tick.swap_utilization;
+        self.swap_total = tick.swap_total;
+
+        // the total as configured where it was recorded
+        self.net_in = tick.net_in;
+        self.net_out = tick.net_out;
+        self.interfaces = tick.interfaces;
+        self.tcp = tick.tcp;
+        self.add_network_values();
+        self.network_interfaces = tick.network_interfaces;
+
+        let mut top = self.new_top();
+        let mut current_pids: HashSet<u32> = HashSet::with_capacity(tick.processes.len());
+        self.threads_total = 0;
+        for zp in tick.processes {
+            self.threads_total += zp.threads_total as usize;
+            top.update(&zp, &self.histogram_map.tick);
+            current_pids.insert(zp.pid);
+            self.process_map.insert(zp.pid, zp);
+        }
+        self.finish_process_list(top, &current_pids, keep_order);
+        self.record_top_consumers();
+
+        self.disks = tick.disks;
+        self.disk_read = tick.disk_read;
+        self.disk_write = tick.disk_write;
+        for disk in self.disks.values() {
+            self.histogram_map.add_value_to(
+                &HistogramKind::FileSystemUsedSpace(disk.name.to_string()),
+                disk.get_used_bytes(),
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::IoRead(disk.name.to_string()),
+                disk.get_read_bytes_sec(&self.histogram_map.tick) as u64,
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::IoWrite(disk.name.to_string()),
+                disk.get_write_bytes_sec(&self.histogram_map.tick) as u64,
+            );
+        }
+
+        self.gfx_devices = tick.gfx_devices;
+        for gd in self.gfx_devices.iter() {
+            self.histogram_map.add_value_to(
+                &HistogramKind::GpuUse(gd.uuid.clone()),
+                gd.gpu_utilization as u64,
+            );
+            self.histogram_map.add_value_to(
+                &HistogramKind::GpuMem(gd.uuid.clone()),
+                gd.mem_utilization as u64,
+            );
+        }
+        self.uptime = tick.uptime;
+    }
+
+    /// Picks up new samples saved by the recorder when viewing its database. The current
+    /// values shown next to the charts are the last recorded ones.
+    pub fn reload_history(&mut self) {
+        self.histogram_map.reload();
+        let histogram_map = &self.histogram_map;
+        let last = |k: &HistogramKind| histogram_map.get(k).and_then(|h| h.last()).unwrap_or(0);
+        self.cpu_utilization = last(&HistogramKind::Cpu);
+        self.mem_utilization = last(&HistogramKind::Mem) * self.mem_total / 100;
+        self.net_in = last(&HistogramKind::NetRx);
+        self.net_out = last(&HistogramKind::NetTx);
+        let mut interfaces: Vec<InterfaceTraffic> = histogram_map
+            .kinds()
+            .filter_map(|k| match k {
+                HistogramKind::NetIfaceRx(name) => Some(InterfaceTraffic {
+                    name: name.clone(),
+                    rx_bytes: last(k),
+                    tx_bytes: last(&HistogramKind::NetIfaceTx(name.clone())),
+                    rx_packets: last(&HistogramKind::NetIfacePacketsRx(name.clone())),
+                    tx_packets: last(&HistogramKind::NetIfacePacketsTx(name.clone())),
+                    rx_errors: last(&HistogramKind::NetIfaceErrorsRx(name.clone())),
+                    tx_errors: last(&HistogramKind::NetIfaceErrorsTx(name.clone())),
+                    rx_drops: last(&HistogramKind::NetIfaceDropsRx(name.clone())),
+                    tx_drops: last(&HistogramKind::NetIfaceDropsTx(name.clone())),
+                    // not recorded, only counts towards the total
+                    is_virtual: false,
+                }),
+                _ => None,
+            })
+            .collect();
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+        self.interfaces = interfaces;
+        self.tcp = histogram_map
+            .get(&HistogramKind::TcpRetransmits)
+            .map(|_| TcpCounters {
+                retransmits: last(&HistogramKind::TcpRetransmits),
+                resets: last(&HistogramKind::TcpResets),
+                listen_overflows: last(&HistogramKind::TcpListenOverflows),
+            });
+    }
+
     pub async fn save_state(&mut self) {
         self.histogram_map.save_histograms();
     }
@@ -865,4 +1232,16 @@ impl CPUTimeApp {
     pub fn writes_db_store(&self) -> bool {
         self.histogram_map.writes_db_store()
     }
+
+    pub fn newer_store(&self) -> Option<u32> {
+        self.histogram_map.newer_store()
+    }
+
+    pub fn other_host(&self) -> Option<&str> {
+        self.histogram_map.other_host()
+    }
+
+    pub fn unreadable_store(&self) -> Option<&str> {
+        self.histogram_map.unreadable_store()
+    }
 }
@@ -0,0 +1,245 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Errors and drops of the network interfaces and the TCP counters of the kernel, read from
+//! `/proc/net` on Linux. The kernel counts from boot, a tick shows the change since the one
+//! before.
+
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Errors and drops of an interface, from `/proc/net/dev`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InterfaceErrors {
+    pub rx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_errors: u64,
+    pub tx_drops: u64,
+}
+
+impl InterfaceErrors {
+    fn since(&self, before: &InterfaceErrors) -> InterfaceErrors {
+        InterfaceErrors {
+            rx_errors: self.rx_errors.saturating_sub(before.rx_errors),
+            rx_drops: self.rx_drops.saturating_sub(before.rx_drops),
+            tx_errors: self.tx_errors.saturating_sub(before.tx_errors),
+            tx_drops: self.tx_drops.saturating_sub(before.tx_drops),
+        }
+    }
+}
+
+/// TCP counters, from `/proc/net/snmp` and `/proc/net/netstat`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TcpCounters {
+    /// Segments sent again.
+    pub retransmits: u64,
+    /// Connections reset from the established or close wait state.
+    pub resets: u64,
+    /// Connections dropped as the accept queue of a listening socket was full.
+    pub listen_overflows: u64,
+}
+
+impl TcpCounters {
+    fn since(&self, before: &TcpCounters) -> TcpCounters {
+        TcpCounters {
+            retransmits: self.retransmits.saturating_sub(before.retransmits),
+            resets: self.resets.saturating_sub(before.resets),
+            listen_overflows: self
+                .listen_overflows
+                .saturating_sub(before.listen_overflows),
+        }
+    }
+}
+
+/// Errors and drops of each interface in the contents of `/proc/net/dev`.
+pub fn parse_net_dev(s: &str) -> HashMap<String, InterfaceErrors> {
+    s.lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (name, counters) = line.split_once(':')?;
+            let counters: Vec<u64> = counters
+                .split_whitespace()
+                .map(|c| c.parse().unwrap_or(0))
+                .collect();
+            // bytes packets errs drop fifo frame compressed multicast, the same for transmit
+            // with colls and carrier in place of frame and multicast
+            let errors = InterfaceErrors {
+                rx_errors: *counters.get(2)?,
+                rx_drops: *counters.get(3)?,
+                tx_errors: *counters.get(10)?,
+                tx_drops: *counters.get(11)?,
+            };
+            Some((name.trim().to_string(), errors))
+        })
+        .collect()
+}
+
+/// Counters of the `prefix` rows in the contents of `/proc/net/snmp` or `/proc/net/netstat`,
+/// by name. A row of names is followed by a row of their values.
+fn parse_counters<'a>(s: &'a str, prefix: &str) -> HashMap<&'a str, u64> {
+    let mut rows = s
+        .lines()
+        .filter_map(|line| line.strip_prefix(prefix)?.strip_prefix(':'));
+    let mut counters = HashMap::new();
+    while let (Some(names), Some(values)) = (rows.next(), rows.next()) {
+        for (name, value) in names.split_whitespace().zip(values.split_whitespace()) {
+            // some are signed, e.g. MaxConn is -1
+            if let Ok(value) = value.parse() {
+                counters.insert(name, value);
+            }
+        }
+    }
+    counters
+}
+
+/// TCP counters in the contents of `/proc/net/snmp` and `/proc/net/netstat`.
+pub fn parse_tcp_counters(snmp: &str, netstat: &str) -> TcpCounters {
+    let tcp = parse_counters(snmp, "Tcp");
+    let ext = parse_counters(netstat, "TcpExt");
+    TcpCounters {
+        retransmits: tcp.get("RetransSegs").copied().unwrap_or(0),
+        resets: tcp.get("EstabResets").copied().unwrap_or(0),
+        listen_overflows: ext.get("ListenOverflows").copied().unwrap_or(0),
+    }
+}
+
+/// The counters as last read, to tell what changed during a tick.
+#[derive(Default)]
+pub struct NetCounters {
+    interfaces: HashMap<String, InterfaceErrors>,
+    tcp: Option<TcpCounters>,
+}
+
+impl NetCounters {
+    /// Errors and drops of each interface and the TCP counters since the last reading. None
+    /// for TCP where they can't be read.
+    pub fn read(&mut self) -> (HashMap<String, InterfaceErrors>, Option<TcpCounters>) {
+        #[cfg(target_os = "linux")]
+        {
+            use std::fs::read_to_string;
+            let interfaces = read_to_string("/proc/net/dev")
+                .map(|s| parse_net_dev(&s))
+                .unwrap_or_default();
+            let tcp = read_to_string("/proc/net/snmp").ok().map(|snmp| {
+                parse_tcp_counters(
+                    &snmp,
+                    &read_to_string("/proc/net/netstat").unwrap_or_default(),
+                )
+            });
+            self.update(interfaces, tcp)
+        }
+        #[cfg(not(target_os = "linux"))]
+        self.update(HashMap::new(), None)
+    }
+
+    /// Takes the counters just read, returning how much they grew. Counters seen for the first
+    /// time haven't grown.
+    fn update(
+        &mut self,
+        interfaces: HashMap<String, InterfaceErrors>,
+        tcp: Option<TcpCounters>,
+    ) -> (HashMap<String, InterfaceErrors>, Option<TcpCounters>) {
+        let grown = interfaces
+            .iter()
+            .map(|(name, now)| {
+                let before = self.interfaces.get(name).unwrap_or(now);
+                (name.clone(), now.since(before))
+            })
+            .collect();
+        let tcp_grown = tcp.map(|now| now.since(self.tcp.as_ref().unwrap_or(&now)));
+        self.interfaces = interfaces;
+        self.tcp = tcp;
+        (grown, tcp_grown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NET_DEV: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo: 137357672   12797    0    0    0     0          0         0 137357672   12797    0    0    0     0       0          0
+  eth0: 90021234  81230    3   17    0     0          0        12  4512345   40123    1    2    0     0       0          0
+";
+
+    #[test]
+    fn test_parse_net_dev() {
+        let errors = parse_net_dev(NET_DEV);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors["lo"], InterfaceErrors::default());
+        assert_eq!(
+            errors["eth0"],
+            InterfaceErrors {
+                rx_errors: 3,
+                rx_drops: 17,
+                tx_errors: 1,
+                tx_drops: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_tcp_counters() {
+        let snmp = "\
+Ip: Forwarding DefaultTTL InReceives
+Ip: 2 64 12845
+Tcp: RtoAlgorithm RtoMin RtoMax MaxConn ActiveOpens PassiveOpens AttemptFails EstabResets CurrEstab InSegs OutSegs RetransSegs InErrs OutRsts InCsumErrors
+Tcp: 1 200 120000 -1 21 20 0 21 8 12813 12811 140 0 9 0
+Udp: InDatagrams NoPorts
+Udp: 32 0
+";
+        let netstat = "\
+TcpExt: SyncookiesSent SyncookiesRecv ListenOverflows ListenDrops
+TcpExt: 0 0 6 6
+IpExt: InNoRoutes InTruncatedPkts
+IpExt: 0 0
+";
+        assert_eq!(
+            parse_tcp_counters(snmp, netstat),
+            TcpCounters {
+                retransmits: 140,
+                resets: 21,
+                listen_overflows: 6,
+            }
+        );
+        assert_eq!(parse_tcp_counters("", ""), TcpCounters::default());
+    }
+
+    #[test]
+    fn test_net_counters_update() {
+        let mut counters = NetCounters::default();
+        let errors = |rx_errors| InterfaceErrors {
+            rx_errors,
+            ..Default::default()
+        };
+        let tcp = |retransmits| TcpCounters {
+            retransmits,
+            ..Default::default()
+        };
+        let (grown, tcp_grown) = counters.update(
+            HashMap::from([("eth0".to_string(), errors(5))]),
+            Some(tcp(100)),
+        );
+        assert_eq!(grown["eth0"], errors(0));
+        assert_eq!(tcp_grown, Some(tcp(0)));
+
+        let (grown, tcp_grown) = counters.update(
+            HashMap::from([
+                ("eth0".to_string(), errors(8)),
+                ("wlan0".to_string(), errors(2)),
+            ]),
+            Some(tcp(130)),
+        );
+        assert_eq!(grown["eth0"], errors(3));
+        assert_eq!(grown["wlan0"], errors(0));
+        assert_eq!(tcp_grown, Some(tcp(30)));
+
+        assert_eq!(
+            counters.update(HashMap::new(), None),
+            (HashMap::new(), None)
+        );
+    }
+}
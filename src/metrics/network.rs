@@ -0,0 +1,217 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Traffic of each network interface and which of them add up to the total.
+
+use serde_derive::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Name prefixes of virtual interfaces, for systems without `/sys/devices/virtual/net`.
+const VIRTUAL_PREFIXES: &[&str] = &[
+    "lo",
+    "docker",
+    "veth",
+    "br-",
+    "virbr",
+    "vnet",
+    "tun",
+    "tap",
+    "wg",
+    "cni",
+    "flannel",
+    "cali",
+    "vxlan",
+    "kube",
+    "tailscale",
+    "utun",
+    "awdl",
+    "llw",
+    "bridge",
+    "gif",
+    "stf",
+    "anpi",
+];
+
+/// Traffic of a network interface during the last tick.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct InterfaceTraffic {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+    /// Loopback, a bridge, a veth pair, a tunnel or another interface without hardware behind.
+    pub is_virtual: bool,
+}
+
+impl InterfaceTraffic {
+    pub fn new(
+        name: &str,
+        rx_bytes: u64,
+        tx_bytes: u64,
+        rx_packets: u64,
+        tx_packets: u64,
+    ) -> InterfaceTraffic {
+        InterfaceTraffic {
+            name: name.to_string(),
+            rx_bytes,
+            tx_bytes,
+            rx_packets,
+            tx_packets,
+            is_virtual: is_virtual(name),
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether the interface `name` of this machine is a virtual one.
+pub fn is_virtual(name: &str) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        use std::path::Path;
+        if Path::new("/sys/class/net").join(name).exists() {
+            return Path::new("/sys/devices/virtual/net").join(name).exists();
+        }
+    }
+    VIRTUAL_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+/// Interfaces whose traffic adds up to the network total.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum NetTotal {
+    /// Interfaces with hardware behind them, so traffic forwarded to containers and VMs or
+    /// tunnelled isn't counted twice. All but loopback if every interface is virtual, as in a
+    /// container.
+    #[default]
+    Physical,
+    All,
+    /// The named interfaces.
+    Interfaces(Vec<String>),
+}
+
+impl NetTotal {
+    fn includes(&self, iface: &InterfaceTraffic, any_physical: bool) -> bool {
+        match self {
+            NetTotal::Physical if any_physical => !iface.is_virtual,
+            NetTotal::Physical => iface.name != "lo" && iface.name != "lo0",
+            NetTotal::All => true,
+            NetTotal::Interfaces(names) => names.contains(&iface.name),
+        }
+    }
+
+    /// Traffic of the interfaces counted in the total added up.
+    pub fn sum(&self, interfaces: &[InterfaceTraffic]) -> InterfaceTraffic {
+        let any_physical = interfaces.iter().any(|i| !i.is_virtual);
+        interfaces
+            .iter()
+            .filter(|i| self.includes(i, any_physical))
+            .fold(InterfaceTraffic::default(), |total, i| InterfaceTraffic {
+                rx_bytes: total.rx_bytes + i.rx_bytes,
+                tx_bytes: total.tx_bytes + i.tx_bytes,
+                rx_packets: total.rx_packets + i.rx_packets,
+                tx_packets: total.tx_packets + i.tx_packets,
+                rx_errors: total.rx_errors + i.rx_errors,
+                tx_errors: total.tx_errors + i.tx_errors,
+                rx_drops: total.rx_drops + i.rx_drops,
+                tx_drops: total.tx_drops + i.tx_drops,
+                ..total
+            })
+    }
+}
+
+impl FromStr for NetTotal {
+    type Err = String;
+
+    /// `physical`, `all` or a comma separated list of interfaces.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "physical" => Ok(NetTotal::Physical),
+            "all" => Ok(NetTotal::All),
+            "" => Err(String::from(
+                "expected physical, all or a list of interfaces",
+            )),
+            names => Ok(NetTotal::Interfaces(
+                names
+                    .split(',')
+                    .map(|n| n.trim().to_string())
+                    .filter(|n| !n.is_empty())
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for NetTotal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NetTotal::Physical => write!(f, "physical"),
+            NetTotal::All => write!(f, "all"),
+            NetTotal::Interfaces(names) => write!(f, "{}", names.join(",")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iface(name: &str, rx_bytes: u64, tx_bytes: u64, is_virtual: bool) -> InterfaceTraffic {
+        InterfaceTraffic {
+            name: name.to_string(),
+            rx_bytes,
+            tx_bytes,
+            rx_drops: rx_bytes / 100,
+            is_virtual,
+            ..Default::default()
+        }
+    }
+
+    fn bytes(total: InterfaceTraffic) -> (u64, u64) {
+        (total.rx_bytes, total.tx_bytes)
+    }
+
+    #[test]
+    fn test_net_total_sum() {
+        let host = vec![
+            iface("docker0", 500, 500, true),
+            iface("eth0", 1000, 200, false),
+            iface("lo", 70, 70, true),
+            iface("veth1a2b", 500, 500, true),
+            iface("wlan0", 10, 20, false),
+        ];
+        assert_eq!(bytes(NetTotal::Physical.sum(&host)), (1010, 220));
+        assert_eq!(NetTotal::Physical.sum(&host).rx_drops, 10);
+        assert_eq!(bytes(NetTotal::All.sum(&host)), (2080, 1290));
+        assert_eq!(
+            bytes(NetTotal::Interfaces(vec!["eth0".to_string(), "lo".to_string()]).sum(&host)),
+            (1070, 270)
+        );
+        // in a container even the uplink is a veth
+        let container = vec![iface("eth0", 300, 100, true), iface("lo", 5, 5, true)];
+        assert_eq!(bytes(NetTotal::Physical.sum(&container)), (300, 100));
+        assert_eq!(bytes(NetTotal::Physical.sum(&[])), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_net_total() {
+        assert_eq!("physical".parse(), Ok(NetTotal::Physical));
+        assert_eq!("all".parse(), Ok(NetTotal::All));
+        assert_eq!(
+            "eth0, wlan0".parse(),
+            Ok(NetTotal::Interfaces(vec![
+                "eth0".to_string(),
+                "wlan0".to_string()
+            ]))
+        );
+        assert!("".parse::<NetTotal>().is_err());
+        assert_eq!(
+            NetTotal::Interfaces(vec!["eth0".to_string(), "wlan0".to_string()]).to_string(),
+            "eth0,wlan0"
+        );
+    }
+}
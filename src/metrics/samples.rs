@@ -0,0 +1,325 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Compressed series of `u64` samples.
+//!
+//! New samples go to an uncompressed tail. Once the tail holds `CHUNK_LEN` samples it is sealed
+//! into a chunk of varints: every sample is written as the zigzag encoded difference to the
+//! previous one, and a repeated value as a zero followed by the number of repeats. Gaps
+//! (`NO_DATA`) are encoded as 0 and other values shifted up by one so the wrapping differences
+//! cover every `u64`. Percentages take a byte per sample and idle or missing series next to
+//! nothing, while reading a range only decodes the chunks it overlaps.
+
+use crate::metrics::histogram::NO_DATA;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const CHUNK_LEN: usize = 512;
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut v = 0u64;
+    let mut shift = 0;
+    loop {
+        let b = *bytes.get(*pos)?;
+        *pos += 1;
+        if shift >= 64 {
+            return None;
+        }
+        v |= u64::from(b & 0x7f) << shift;
+        if b & 0x80 == 0 {
+            return Some(v);
+        }
+        shift += 7;
+    }
+}
+
+fn zigzag(d: u64) -> u64 {
+    let d = d as i64;
+    ((d << 1) ^ (d >> 63)) as u64
+}
+
+fn unzigzag(z: u64) -> u64 {
+    (z >> 1) ^ (z & 1).wrapping_neg()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct Chunk {
+    len: u32,
+    bytes: Vec<u8>,
+}
+
+impl Chunk {
+    fn encode(samples: &[u64]) -> Chunk {
+        let mut bytes = vec![];
+        let mut prev = 0u64;
+        let mut i = 0;
+        while i < samples.len() {
+            let x = samples[i].wrapping_add(1);
+            let run = samples[i..]
+                .iter()
+                .take_while(|v| **v == samples[i])
+                .count();
+            let mut repeats = run;
+            if x != prev {
+                write_varint(&mut bytes, zigzag(x.wrapping_sub(prev)));
+                prev = x;
+                repeats -= 1;
+            }
+            if repeats > 0 {
+                bytes.push(0);
+                write_varint(&mut bytes, repeats as u64 - 1);
+            }
+            i += run;
+        }
+        bytes.shrink_to_fit();
+        Chunk {
+            len: samples.len() as u32,
+            bytes,
+        }
+    }
+
+    /// Runs of equal samples as value and count. A damaged chunk ends early.
+    fn runs(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let mut pos = 0;
+        let mut prev = 0u64;
+        std::iter::from_fn(move || {
+            let token = read_varint(&self.bytes, &mut pos)?;
+            if token == 0 {
+                let repeats = read_varint(&self.bytes, &mut pos)?;
+                Some((prev.wrapping_sub(1), repeats.saturating_add(1)))
+            } else {
+                prev = prev.wrapping_add(unzigzag(token));
+                Some((prev.wrapping_sub(1), 1))
+            }
+        })
+    }
+
+    /// All samples of the chunk, padded with gaps if it is damaged.
+    fn decode(&self) -> Vec<u64> {
+        let len = self.len as usize;
+        let mut out = Vec::with_capacity(len);
+        for (v, n) in self.runs() {
+            let n = n.min((len - out.len()) as u64) as usize;
+            out.extend(std::iter::repeat_n(v, n));
+            if out.len() == len {
+                break;
+            }
+        }
+        out.resize(len, NO_DATA);
+        out
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Samples {
+    chunks: VecDeque<Chunk>,
+    /// Samples at the start of the oldest chunk that were already dropped.
+    skip: usize,
+    tail: Vec<u64>,
+}
+
+impl From<Vec<u64>> for Samples {
+    fn from(data: Vec<u64>) -> Self {
+        let mut samples = Samples::default();
+        for c in data.chunks(CHUNK_LEN) {
+            samples.extend_from_slice(c);
+        }
+        samples
+    }
+}
+
+impl Samples {
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(|c| c.len as usize).sum::<usize>() - self.skip + self.tail.len()
+    }
+
+    fn seal(&mut self) {
+        if self.tail.len() >= CHUNK_LEN {
+            self.chunks.push_back(Chunk::encode(&self.tail));
+            self.tail.clear();
+        }
+    }
+
+    fn extend_from_slice(&mut self, data: &[u64]) {
+        self.tail.extend_from_slice(data);
+        self.seal();
+    }
+
+    /// Appends `n` copies of `val`.
+    pub fn push(&mut self, val: u64, mut n: usize) {
+        while n > 0 {
+            let take = n.min(CHUNK_LEN - self.tail.len());
+            self.tail.extend(std::iter::repeat_n(val, take));
+            self.seal();
+            n -= take;
+        }
+    }
+
+    /// Drops the oldest samples so at most `keep` are left.
+    pub fn truncate_front(&mut self, keep: usize) {
+        let mut drop = self.len().saturating_sub(keep);
+        while drop > 0 {
+            match self.chunks.front() {
+                Some(c) => {
+                    let left = c.len as usize - self.skip;
+                    if drop >= left {
+                        self.chunks.pop_front();
+                        self.skip = 0;
+                        drop -= left;
+                    } else {
+                        self.skip += drop;
+                        drop = 0;
+                    }
+                }
+                None => {
+                    self.tail.drain(0..drop);
+                    drop = 0;
+                }
+            }
+        }
+    }
+
+    /// Samples `start..end`, oldest first.
+    pub fn range(&self, start: usize, end: usize) -> Vec<u64> {
+        let end = end.min(self.len());
+        if start >= end {
+            return vec![];
+        }
+        let mut out = Vec::with_capacity(end - start);
+        // positions counted from the first chunk, including the skipped samples
+        let (start, end) = (start + self.skip, end + self.skip);
+        let mut pos = 0;
+        for c in self.chunks.iter() {
+            let len = c.len as usize;
+            if pos + len > start && pos < end {
+                let data = c.decode();
+                out.extend_from_slice(&data[start.max(pos) - pos..end.min(pos + len) - pos]);
+            }
+            pos += len;
+            if pos >= end {
+                return out;
+            }
+        }
+        if end > pos {
+            out.extend_from_slice(&self.tail[start.max(pos) - pos..end - pos]);
+        }
+        out
+    }
+
+    /// All samples, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.chunks
+            .iter()
+            .enumerate()
+            .flat_map(move |(i, c)| {
+                let skip = if i == 0 { self.skip } else { 0 };
+                c.decode().into_iter().skip(skip)
+            })
+            .chain(self.tail.iter().copied())
+    }
+
+    pub fn get(&self, i: usize) -> Option<u64> {
+        self.range(i, i + 1).first().copied()
+    }
+
+    pub fn to_vec(&self) -> Vec<u64> {
+        self.iter().collect()
+    }
+
+    pub fn last(&self) -> Option<u64> {
+        match self.tail.last() {
+            Some(v) => Some(*v),
+            None => self.chunks.back().and_then(|c| c.decode().last().copied()),
+        }
+    }
+
+    /// Index of the first sample matching `pred`. Runs are tested once, so long stretches of
+    /// the same value are skipped without decoding them.
+    pub fn position(&self, mut pred: impl FnMut(u64) -> bool) -> Option<usize> {
+        let mut pos = 0usize;
+        for (i, c) in self.chunks.iter().enumerate() {
+            let skip = if i == 0 { self.skip } else { 0 };
+            let mut offset = 0usize;
+            for (v, n) in c.runs() {
+                let n = n.min(c.len as u64 - offset as u64) as usize;
+                let end = offset + n;
+                if end > skip && pred(v) {
+                    return Some(pos + offset.max(skip) - skip);
+                }
+                offset = end;
+                if offset == c.len as usize {
+                    break;
+                }
+            }
+            pos += c.len as usize - skip;
+        }
+        self.tail.iter().position(|v| pred(*v)).map(|p| pos + p)
+    }
+
+    /// Bytes used by the samples, for reporting.
+    pub fn size_bytes(&self) -> usize {
+        self.chunks.iter().map(|c| c.bytes.len()).sum::<usize>()
+            + self.tail.len() * std::mem::size_of::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_round_trip() {
+        let mut data: Vec<u64> = (0..2000u64).map(|i| (i * 7) % 101).collect();
+        data[10] = NO_DATA;
+        data[600..900].fill(NO_DATA);
+        data[1000..1200].fill(500);
+        data[1500] = 0;
+        data[1501] = u64::MAX - 1;
+        data[1502] = 0;
+        let samples = Samples::from(data.clone());
+        assert_eq!(samples.len(), data.len());
+        assert_eq!(samples.to_vec(), data);
+        assert_eq!(samples.range(500, 1600), &data[500..1600]);
+        assert_eq!(samples.range(1990, 2000), &data[1990..]);
+        assert_eq!(samples.last(), data.last().copied());
+        assert_eq!(samples.position(|v| v == 500), Some(1000));
+        assert_eq!(samples.position(|v| v == 1000), None);
+    }
+
+    #[test]
+    fn test_samples_truncate_front() {
+        let mut samples = Samples::default();
+        samples.push(NO_DATA, 700);
+        for v in 0..900u64 {
+            samples.push(v % 100, 1);
+        }
+        samples.truncate_front(1000);
+        assert_eq!(samples.len(), 1000);
+        assert_eq!(samples.position(|v| v != NO_DATA), Some(100));
+        assert_eq!(samples.range(100, 103), vec![0, 1, 2]);
+        samples.truncate_front(10);
+        assert_eq!(
+            samples.to_vec(),
+            (890..900u64).map(|v| v % 100).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_samples_compress() {
+        // a day of CPU percentages at the default tick and an idle disk
+        let cpu = Samples::from((0..43200u64).map(|i| 20 + (i * 13) % 9).collect::<Vec<_>>());
+        assert!(cpu.size_bytes() * 6 < cpu.len() * 8);
+        let mut idle = Samples::default();
+        idle.push(0, 43200);
+        assert!(idle.size_bytes() * 100 < idle.len() * 8);
+    }
+}
@@ -0,0 +1,527 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Recordings of everything zenith samples, written with `--record` and played back with
+//! `--replay`.
+//!
+//! A recording is a file of journal frames. The first holds a format header followed by the
+//! tick and the host, every other one the metrics of one tick, bincode encoded and gzip
+//! compressed. The names and command lines of a process are only written in the first tick
+//! it appears in or after they changed; the ticks after that carry them over.
+
+use crate::metrics::disk::ZDisk;
+use crate::metrics::graphics::device::GraphicsDevice;
+use crate::metrics::journal;
+use crate::metrics::netstat::TcpCounters;
+use crate::metrics::network::InterfaceTraffic;
+use crate::metrics::zprocess::ZProcess;
+use crate::metrics::{CPUTimeApp, NetworkInterface, Sensor};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const SESSION_MAGIC: &[u8; 4] = b"ZREC";
+/// Format of the recordings this version writes and reads.
+pub const SESSION_VERSION: u32 = 1;
+
+/// Playback speeds, as multiples of the recorded tick.
+const SPEEDS: &[f64] = &[0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0];
+const NORMAL_SPEED: usize = 2;
+/// Shortest tick zenith samples at, see `--refresh-rate`.
+const MIN_TICK: Duration = Duration::from_millis(1000);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Host {
+    pub hostname: String,
+    pub osname: String,
+    pub release: String,
+    pub version: String,
+    pub arch: String,
+    pub processor_name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    /// Version of zenith that made the recording.
+    zenith: String,
+    tick: Duration,
+    host: Host,
+}
+
+/// What identifies a process, left out of the ticks after the one it appeared in.
+#[derive(PartialEq)]
+struct Identity {
+    start_time: u64,
+    user_name: String,
+    name: String,
+    exe: String,
+    command: Vec<String>,
+}
+
+impl Identity {
+    fn of(p: &ZProcess) -> Identity {
+        Identity {
+            start_time: p.start_time,
+            user_name: p.user_name.clone(),
+            name: p.name.clone(),
+            exe: p.exe.clone(),
+            command: p.command.clone(),
+        }
+    }
+
+    fn clear(p: &mut ZProcess) {
+        p.user_name.clear();
+        p.name.clear();
+        p.exe.clear();
+        p.command.clear();
+    }
+
+    fn restore(&self, p: &mut ZProcess) {
+        p.user_name = self.user_name.clone();
+        p.name = self.name.clone();
+        p.exe = self.exe.clone();
+        p.command = self.command.clone();
+    }
+}
+
+/// The metrics of one tick.
+#[derive(Serialize, Deserialize)]
+pub struct Tick {
+    pub time: SystemTime,
+    pub cpu_utilization: u64,
+    pub cpus: Vec<(String, u64)>,
+    pub frequency: u64,
+    pub mem_utilization: u64,
+    pub mem_total: u64,
+    pub swap_utilization: u64,
+    pub swap_total: u64,
+    pub disks: HashMap<String, ZDisk>,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub net_in: u64,
+    pub net_out: u64,
+    pub network_interfaces: Vec<NetworkInterface>,
+    pub interfaces: Vec<InterfaceTraffic>,
+    pub tcp: Option<TcpCounters>,
+    pub processes: Vec<ZProcess>,
+    /// Pids whose identity is the one written before, left empty in `processes`.
+    carried: Vec<u32>,
+    pub sensors: Vec<Sensor>,
+    pub gfx_devices: Vec<GraphicsDevice>,
+    pub uptime: Duration,
+}
+
+fn invalid_data<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+pub struct Recorder {
+    path: PathBuf,
+    out: BufWriter<File>,
+    started: bool,
+    /// Identities written so far, by pid.
+    written: HashMap<u32, Identity>,
+}
+
+impl Recorder {
+    /// Starts a recording at `path`, replacing any file there.
+    pub fn create(path: &Path) -> io::Result<Recorder> {
+        Ok(Recorder {
+            path: path.to_owned(),
+            out: BufWriter::new(File::create(path)?),
+            started: false,
+            written: HashMap::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends the metrics `app` gathered this tick.
+    pub fn record(&mut self, app: &CPUTimeApp) -> io::Result<()> {
+        if !self.started {
+            let header = Header {
+                zenith: env!("CARGO_PKG_VERSION").to_string(),
+                tick: app.histogram_map.tick,
+                host: Host {
+                    hostname: app.hostname.clone(),
+                    osname: app.osname.clone(),
+                    release: app.release.clone(),
+                    version: app.version.clone(),
+                    arch: app.arch.clone(),
+                    processor_name: app.processor_name.clone(),
+                },
+            };
+            let mut payload = SESSION_MAGIC.to_vec();
+            payload.extend_from_slice(&SESSION_VERSION.to_le_bytes());
+            payload.extend(bincode::serialize(&header).map_err(invalid_data)?);
+            journal::write_frame(&mut self.out, &payload)?;
+            self.started = true;
+        }
+
+        let mut written = HashMap::with_capacity(app.process_map.len());
+        let mut carried = vec![];
+        let processes = app
+            .process_map
+            .values()
+            .map(|p| {
+                let mut p = p.clone();
+                let identity = Identity::of(&p);
+                if self.written.get(&p.pid) == Some(&identity) {
+                    carried.push(p.pid);
+                    Identity::clear(&mut p);
+                }
+                written.insert(p.pid, identity);
+                p
+            })
+            .collect();
+        self.written = written;
+
+        let tick = Tick {
+            time: app.histogram_map.time_at(0).unwrap_or_else(SystemTime::now),
+            cpu_utilization: app.cpu_utilization,
+            cpus: app.cpus.clone(),
+            frequency: app.frequency,
+            mem_utilization: app.mem_utilization,
+            mem_total: app.mem_total,
+            swap_utilization: app.swap_utilization,
+            swap_total: app.swap_total,
+            disks: app.disks.clone(),
+            disk_read: app.disk_read,
+            disk_write: app.disk_write,
+            net_in: app.net_in,
+            net_out: app.net_out,
+            network_interfaces: app.network_interfaces.clone(),
+            interfaces: app.interfaces.clone(),
+            tcp: app.tcp,
+            processes,
+            carried,
+            sensors: app.sensors.clone(),
+            gfx_devices: app.gfx_devices.clone(),
+            uptime: app.uptime,
+        };
+        let mut gz = GzEncoder::new(vec![], Compression::default());
+        gz.write_all(&bincode::serialize(&tick).map_err(invalid_data)?)?;
+        journal::write_frame(&mut self.out, &gz.finish()?)?;
+        self.out.flush()
+    }
+}
+
+/// Reads a recording back one tick at a time.
+pub struct Player {
+    path: PathBuf,
+    input: BufReader<File>,
+    tick: Duration,
+    host: Host,
+    identities: HashMap<u32, Identity>,
+    paused: bool,
+    speed: usize,
+    /// Ticks owed to the playback at its speed, less than one tick is carried to the next.
+    due: f64,
+    ended: bool,
+}
+
+impl Player {
+    /// Opens the recording at `path` and reads its header.
+    pub fn open(path: &Path) -> io::Result<Player> {
+        let mut input = BufReader::new(File::open(path)?);
+        let header = journal::read_frame(&mut input)?
+            .ok_or_else(|| invalid_data("not a zenith recording"))?;
+        if header.len() < 8 || &header[..4] != SESSION_MAGIC {
+            return Err(invalid_data("not a zenith recording"));
+        }
+        let mut version = [0; 4];
+        version.copy_from_slice(&header[4..8]);
+        let version = u32::from_le_bytes(version);
+        if version > SESSION_VERSION {
+            return Err(invalid_data(format!(
+                "recording format {} was written by a newer zenith, this version supports up to {}",
+                version, SESSION_VERSION
+            )));
+        }
+        let header: Header = bincode::deserialize(&header[8..]).map_err(invalid_data)?;
+        if header.tick < MIN_TICK {
+            // zenith never records that fast, the header is damaged
+            return Err(invalid_data(format!(
+                "recording has a tick of {} ms, less than {} ms",
+                header.tick.as_millis(),
+                MIN_TICK.as_millis()
+            )));
+        }
+        debug!("Replaying a recording by zenith {}", header.zenith);
+        Ok(Player {
+            path: path.to_owned(),
+            input,
+            tick: header.tick,
+            host: header.host,
+            identities: HashMap::new(),
+            paused: false,
+            speed: NORMAL_SPEED,
+            due: 0.0,
+            ended: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Tick of the recording.
+    pub fn tick(&self) -> Duration {
+        self.tick
+    }
+
+    pub fn host(&self) -> &Host {
+        &self.host
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.due = 0.0;
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// Ticks to play now that one tick of the recording's length has passed.
+    pub fn due(&mut self) -> usize {
+        if self.paused || self.ended {
+            return 0;
+        }
+        self.due += self.speed();
+        let n = self.due.floor();
+        self.due -= n;
+        n as usize
+    }
+
+    /// The next tick of the recording, `None` once it ended. A damaged tick ends it too, as a
+    /// recording cut short by a crash ends in an incomplete frame.
+    pub fn next_tick(&mut self) -> Option<Tick> {
+        if self.ended {
+            return None;
+        }
+        match self.read_tick() {
+            Ok(Some(tick)) => Some(tick),
+            Ok(None) => {
+                self.ended = true;
+                None
+            }
+            Err(e) => {
+                warn!("{}: {}", self.path.display(), e);
+                self.ended = true;
+                None
+            }
+        }
+    }
+
+    fn read_tick(&mut self) -> io::Result<Option<Tick>> {
+        let frame = match journal::read_frame(&mut self.input)? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+        let mut data = vec![];
+        GzDecoder::new(frame.as_slice()).read_to_end(&mut data)?;
+        let mut tick: Tick = bincode::deserialize(&data).map_err(invalid_data)?;
+
+        let carried: HashSet<u32> = tick.carried.drain(..).collect();
+        let mut identities = HashMap::with_capacity(tick.processes.len());
+        for p in tick.processes.iter_mut() {
+            if carried.contains(&p.pid) {
+                if let Some(identity) = self.identities.get(&p.pid) {
+                    identity.restore(p);
+                }
+            }
+            identities.insert(p.pid, Identity::of(p));
+        }
+        self.identities = identities;
+        Ok(Some(tick))
+    }
+}
+
+/// Where the metrics come from.
+pub enum Session {
+    /// Sampled from this machine.
+    Live,
+    /// Sampled from this machine and written to a recording.
+    Record(Recorder),
+    /// Read from a recording.
+    Replay(Player),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::histogram::Retention;
+    use crate::metrics::zprocess::tests::create_test_process;
+
+    fn process(pid: u32, name: &str) -> ZProcess {
+        let mut p = create_test_process();
+        p.pid = pid;
+        p.name = name.to_string();
+        p.command = vec![format!("/usr/bin/{}", name), "-v".to_string()];
+        p
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path = std::env::temp_dir().join(format!("zenith-test-{}.rec", std::process::id()));
+        let tick = Duration::from_secs(2);
+        let mut app = CPUTimeApp::new(tick, None, false, Retention::default());
+        app.hostname = "db1".to_string();
+
+        let mut recorder = Recorder::create(&path).unwrap();
+        for i in 0..3u64 {
+            app.histogram_map
+                .begin_tick(SystemTime::UNIX_EPOCH + Duration::from_secs(100 + i * 2));
+            app.cpu_utilization = 10 * i;
+            app.interfaces = vec![InterfaceTraffic {
+                name: "eth0".to_string(),
+                rx_bytes: 1000 * i,
+                ..Default::default()
+            }];
+            app.tcp = Some(TcpCounters {
+                retransmits: i,
+                ..Default::default()
+            });
+            app.process_map.clear();
+            app.process_map.insert(1, process(1, "init"));
+            let mut worker = process(7, "worker");
+            worker.net_tx_bytes = 500 * i;
+            if i == 2 {
+                // exec'd, same pid with another name
+                worker.name = "rsync".to_string();
+            }
+            app.process_map.insert(7, worker);
+            recorder.record(&app).unwrap();
+        }
+        drop(recorder);
+
+        let mut player = Player::open(&path).unwrap();
+        assert_eq!(player.tick(), tick);
+        assert_eq!(player.host().hostname, "db1");
+        let ticks: Vec<Tick> = std::iter::from_fn(|| player.next_tick()).collect();
+        assert!(player.ended());
+        assert_eq!(ticks.len(), 3);
+        assert_eq!(ticks[1].cpu_utilization, 10);
+        assert_eq!(ticks[2].interfaces[0].name, "eth0");
+        assert_eq!(ticks[2].interfaces[0].rx_bytes, 2000);
+        assert_eq!(ticks[2].tcp.unwrap().retransmits, 2);
+        let worker = ticks[2].processes.iter().find(|p| p.pid == 7).unwrap();
+        assert_eq!(worker.net_tx_bytes, 1000);
+        assert_eq!(
+            ticks[1].time,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(102)
+        );
+        for t in ticks.iter() {
+            let init = t.processes.iter().find(|p| p.pid == 1).unwrap();
+            assert_eq!(init.name, "init");
+            assert_eq!(init.command, vec!["/usr/bin/init", "-v"]);
+        }
+        let names: Vec<&str> = ticks
+            .iter()
+            .map(|t| {
+                t.processes
+                    .iter()
+                    .find(|p| p.pid == 7)
+                    .unwrap()
+                    .name
+                    .as_str()
+            })
+            .collect();
+        assert_eq!(names, vec!["worker", "worker", "rsync"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_recording() {
+        let path = std::env::temp_dir().join(format!("zenith-test-{}.cut", std::process::id()));
+        let mut app = CPUTimeApp::new(Duration::from_secs(1), None, false, Retention::default());
+        let mut recorder = Recorder::create(&path).unwrap();
+        for i in 0..3u64 {
+            app.cpu_utilization = 10 * i;
+            recorder.record(&app).unwrap();
+        }
+        drop(recorder);
+        // zenith was killed while writing the last tick
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 5)
+            .unwrap();
+
+        let mut player = Player::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let ticks: Vec<Tick> = std::iter::from_fn(|| player.next_tick()).collect();
+        assert!(player.ended());
+        assert_eq!(player.due(), 0);
+        let cpu: Vec<u64> = ticks.iter().map(|t| t.cpu_utilization).collect();
+        assert_eq!(cpu, vec![0, 10]);
+    }
+
+    #[test]
+    fn test_player_speed() {
+        let path = std::env::temp_dir().join(format!("zenith-test-{}.speed", std::process::id()));
+        let app = CPUTimeApp::new(Duration::from_secs(1), None, false, Retention::default());
+        Recorder::create(&path).unwrap().record(&app).unwrap();
+        let mut player = Player::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(player.due(), 1);
+        player.slower();
+        player.slower();
+        let due: usize = (0..8).map(|_| player.due()).sum();
+        assert_eq!(due, 2);
+        player.faster();
+        player.faster();
+        player.faster();
+        assert_eq!(player.due(), 2);
+        player.toggle_pause();
+        assert_eq!(player.due(), 0);
+    }
+
+    #[test]
+    fn test_not_a_recording() {
+        let path = std::env::temp_dir().join(format!("zenith-test-{}.bad", std::process::id()));
+        std::fs::write(&path, b"hello").unwrap();
+        assert!(Player::open(&path).is_err());
+        let mut frame = vec![];
+        journal::write_frame(&mut frame, b"ZNTH\x07\0\0\0").unwrap();
+        std::fs::write(&path, &frame).unwrap();
+        assert!(Player::open(&path).is_err());
+
+        // a tick zenith never records at
+        let app = CPUTimeApp::new(Duration::from_millis(10), None, false, Retention::default());
+        Recorder::create(&path).unwrap().record(&app).unwrap();
+        let e = Player::open(&path).err().unwrap();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Snapshots of the process table, kept in memory so the table can be shown as it was at a
+//! tick of the history.
+//!
+//! A snapshot only keeps the columns of the table. The names and command lines of a process
+//! rarely change, so they are shared by all snapshots of it. Once the cap is reached every
+//! other snapshot of the older half is dropped: recent ticks keep one snapshot each while
+//! older ones are kept at a lower resolution the further back they are.
+
+use crate::metrics::zprocess::ZProcess;
+use crate::metrics::{sort_pids, ProcessTableSortBy, ProcessTableSortOrder, Top};
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+use sysinfo::ProcessStatus;
+
+/// What identifies a process to the reader, shared between its snapshots.
+struct Identity {
+    user_name: String,
+    name: String,
+    exe: String,
+    command: Vec<String>,
+}
+
+struct Row {
+    pid: u32,
+    uid: u32,
+    identity: Rc<Identity>,
+    status: ProcessStatus,
+    priority: i32,
+    nice: i32,
+    threads_total: u32,
+    start_time: u64,
+    cpu_usage: f32,
+    memory: u64,
+    virtual_memory: u64,
+    read_bytes_sec: f32,
+    write_bytes_sec: f32,
+    io_wait: f32,
+    net_rx_bytes_sec: f32,
+    net_tx_bytes_sec: f32,
+    gpu_usage: u32,
+    fb_utilization: u32,
+}
+
+struct Snapshot {
+    time: SystemTime,
+    rows: Vec<Row>,
+}
+
+/// The process table at one tick, ready to be sorted and shown like the live one.
+pub struct ProcessView {
+    pub time: SystemTime,
+    pub process_map: HashMap<u32, ZProcess>,
+    pub processes: Vec<u32>,
+    pub top_pids: Top,
+    pub threads_total: usize,
+}
+
+pub struct ProcessSnapshots {
+    cap: usize,
+    snapshots: VecDeque<Snapshot>,
+    identities: HashMap<(u32, u64), Rc<Identity>>,
+}
+
+impl ProcessSnapshots {
+    /// Keeps at most `cap` snapshots, none if it is 0.
+    pub fn new(cap: usize) -> Self {
+        ProcessSnapshots {
+            cap,
+            snapshots: VecDeque::new(),
+            identities: HashMap::new(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.cap > 0
+    }
+
+    /// Records the processes as they were at `time`.
+    pub fn record<'a>(
+        &mut self,
+        time: SystemTime,
+        processes: impl Iterator<Item = &'a ZProcess>,
+        tick: &Duration,
+    ) {
+        if !self.enabled() {
+            return;
+        }
+        let rows = processes
+            .map(|p| Row {
+                pid: p.pid,
+                uid: p.uid,
+                identity: self.identity(p),
+                status: p.status,
+                priority: p.priority,
+                nice: p.nice,
+                threads_total: p.threads_total as u32,
+                start_time: p.start_time,
+                cpu_usage: p.cpu_usage,
+                memory: p.memory,
+                virtual_memory: p.virtual_memory,
+                read_bytes_sec: p.get_read_bytes_sec(tick) as f32,
+                write_bytes_sec: p.get_write_bytes_sec(tick) as f32,
+                #[cfg(target_os = "linux")]
+                io_wait: p.get_io_wait(tick) as f32,
+                #[cfg(not(target_os = "linux"))]
+                io_wait: 0.0,
+                net_rx_bytes_sec: p.get_net_rx_bytes_sec(tick) as f32,
+                net_tx_bytes_sec: p.get_net_tx_bytes_sec(tick) as f32,
+                gpu_usage: p.gpu_usage as u32,
+                fb_utilization: p.fb_utilization as u32,
+            })
+            .collect();
+        self.snapshots.push_back(Snapshot { time, rows });
+        if self.snapshots.len() >= self.cap {
+            self.thin();
+        }
+    }
+
+    fn identity(&mut self, p: &ZProcess) -> Rc<Identity> {
+        let key = (p.pid, p.start_time);
+        if let Some(known) = self.identities.get(&key) {
+            // a process keeps its pid and start time across an exec
+            if known.name == p.name
+                && known.command == p.command
+                && known.exe == p.exe
+                && known.user_name == p.user_name
+            {
+                return known.clone();
+            }
+        }
+        let identity = Rc::new(Identity {
+            user_name: p.user_name.clone(),
+            name: p.name.clone(),
+            exe: p.exe.clone(),
+            command: p.command.clone(),
+        });
+        self.identities.insert(key, identity.clone());
+        identity
+    }
+
+    /// Drops every other snapshot of the older half.
+    fn thin(&mut self) {
+        let older = self.snapshots.len() / 2;
+        let mut i = 0;
+        self.snapshots.retain(|_| {
+            i += 1;
+            i > older || i % 2 == 0
+        });
+        // identities of processes that are in no snapshot anymore
+        self.identities
+            .retain(|_, identity| Rc::strong_count(identity) > 1);
+    }
+
+    /// The process table as it was at `time`, from the newest snapshot taken at or before it.
+    pub fn at(
+        &self,
+        time: SystemTime,
+        tick: &Duration,
+        sortby: ProcessTableSortBy,
+        sortorder: ProcessTableSortOrder,
+    ) -> Option<ProcessView> {
+        let i = self.snapshots.partition_point(|s| s.time <= time);
+        let snapshot = self.snapshots.get(i.checked_sub(1)?)?;
+        let mut top_pids = Top::default();
+        let mut threads_total = 0;
+        let process_map: HashMap<u32, ZProcess> = snapshot
+            .rows
+            .iter()
+            .map(|r| {
+                let p = r.to_process(snapshot.time, tick);
+                top_pids.update(&p, tick);
+                threads_total += p.threads_total as usize;
+                (p.pid, p)
+            })
+            .collect();
+        let mut processes: Vec<u32> = process_map.keys().copied().collect();
+        sort_pids(&mut processes, &process_map, sortby, sortorder, tick);
+        Some(ProcessView {
+            time: snapshot.time,
+            process_map,
+            processes,
+            top_pids,
+            threads_total,
+        })
+    }
+}
+
+impl Row {
+    /// A process whose rates over `tick` come out as recorded.
+    fn to_process(&self, time: SystemTime, tick: &Duration) -> ZProcess {
+        let secs = tick.as_secs_f64();
+        let read_bytes = (self.read_bytes_sec as f64 * secs) as u64;
+        let write_bytes = (self.write_bytes_sec as f64 * secs) as u64;
+        let io_delay = Duration::from_secs_f64((self.io_wait as f64 / 100.0 * secs).max(0.0));
+        let net_rx_bytes = (self.net_rx_bytes_sec as f64 * secs) as u64;
+        let net_tx_bytes = (self.net_tx_bytes_sec as f64 * secs) as u64;
+        ZProcess {
+            pid: self.pid,
+            uid: self.uid,
+            user_name: self.identity.user_name.clone(),
+            memory: self.memory,
+            cpu_usage: self.cpu_usage,
+            cum_cpu_usage: self.cpu_usage as f64,
+            command: self.identity.command.clone(),
+            exe: self.identity.exe.clone(),
+            status: self.status,
+            name: self.identity.name.clone(),
+            priority: self.priority,
+            nice: self.nice,
+            virtual_memory: self.virtual_memory,
+            threads_total: self.threads_total as u64,
+            read_bytes,
+            write_bytes,
+            prev_read_bytes: 0,
+            prev_write_bytes: 0,
+            last_updated: time,
+            end_time: None,
+            start_time: self.start_time,
+            gpu_usage: self.gpu_usage as u64,
+            fb_utilization: self.fb_utilization as u64,
+            enc_utilization: 0,
+            dec_utilization: 0,
+            sm_utilization: 0,
+            io_delay,
+            swap_delay: Duration::ZERO,
+            prev_io_delay: Duration::ZERO,
+            prev_swap_delay: Duration::ZERO,
+            peak_cpu_usage: self.cpu_usage,
+            peak_memory: self.memory,
+            peak_read_bytes_sec: read_bytes as f64 / secs,
+            peak_write_bytes_sec: write_bytes as f64 / secs,
+            peak_gpu_usage: self.gpu_usage as u64,
+            peak_fb_utilization: self.fb_utilization as u64,
+            net_rx_bytes,
+            net_tx_bytes,
+            peak_net_rx_bytes_sec: net_rx_bytes as f64 / secs,
+            peak_net_tx_bytes_sec: net_tx_bytes as f64 / secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A process read at `tick` with the given rates.
+    fn process(
+        pid: u32,
+        name: &str,
+        cpu_usage: f32,
+        read_bytes_sec: f32,
+        tick: &Duration,
+    ) -> ZProcess {
+        Row {
+            pid,
+            uid: 0,
+            identity: Rc::new(Identity {
+                user_name: "root".to_string(),
+                name: name.to_string(),
+                exe: format!("/usr/bin/{}", name),
+                command: vec![name.to_string()],
+            }),
+            status: ProcessStatus::Run,
+            priority: 20,
+            nice: 0,
+            threads_total: 2,
+            start_time: 100 + pid as u64,
+            cpu_usage,
+            memory: 4096,
+            virtual_memory: 8192,
+            read_bytes_sec,
+            write_bytes_sec: 0.0,
+            io_wait: 0.0,
+            net_rx_bytes_sec: 0.0,
+            net_tx_bytes_sec: 0.0,
+            gpu_usage: 0,
+            fb_utilization: 0,
+        }
+        .to_process(SystemTime::UNIX_EPOCH, tick)
+    }
+
+    #[test]
+    fn test_process_snapshots_at() {
+        let tick = Duration::from_secs(2);
+        let t = |s| SystemTime::UNIX_EPOCH + Duration::from_secs(s);
+        let mut snapshots = ProcessSnapshots::new(10);
+        let make = process(1, "make", 10.0, 0.0, &tick);
+        let tar = process(2, "tar", 1.0, 2048.0, &tick);
+        snapshots.record(t(10), vec![&make].into_iter(), &tick);
+        snapshots.record(t(12), vec![&make, &tar].into_iter(), &tick);
+
+        assert!(snapshots
+            .at(
+                t(9),
+                &tick,
+                ProcessTableSortBy::Cpu,
+                ProcessTableSortOrder::Descending
+            )
+            .is_none());
+        let view = snapshots
+            .at(
+                t(11),
+                &tick,
+                ProcessTableSortBy::Cpu,
+                ProcessTableSortOrder::Descending,
+            )
+            .unwrap();
+        assert_eq!(view.time, t(10));
+        assert_eq!(view.processes, vec![1]);
+
+        let view = snapshots
+            .at(
+                t(20),
+                &tick,
+                ProcessTableSortBy::Cpu,
+                ProcessTableSortOrder::Ascending,
+            )
+            .unwrap();
+        assert_eq!(view.processes, vec![2, 1]);
+        assert_eq!(view.threads_total, 4);
+        assert_eq!(view.top_pids.read.pid, Some(2));
+        assert_eq!(view.process_map[&2].get_read_bytes_sec(&tick), 2048.0);
+        assert_eq!(view.process_map[&2].name, "tar");
+        // both snapshots share the identity of make
+        assert_eq!(snapshots.identities.len(), 2);
+    }
+
+    #[test]
+    fn test_process_snapshots_thin() {
+        let tick = Duration::from_secs(1);
+        let t = |s| SystemTime::UNIX_EPOCH + Duration::from_secs(s);
+        let mut snapshots = ProcessSnapshots::new(8);
+        for s in 0..7 {
+            let p = process(s as u32, "sleep", 0.0, 0.0, &tick);
+            snapshots.record(t(s), vec![&p].into_iter(), &tick);
+        }
+        assert_eq!(snapshots.snapshots.len(), 7);
+        let p = process(7, "sleep", 0.0, 0.0, &tick);
+        snapshots.record(t(7), vec![&p].into_iter(), &tick);
+        let times: Vec<SystemTime> = snapshots.snapshots.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![t(1), t(3), t(4), t(5), t(6), t(7)]);
+        assert_eq!(snapshots.identities.len(), 6);
+
+        assert!(ProcessSnapshots::new(0)
+            .at(
+                t(0),
+                &tick,
+                ProcessTableSortBy::Cpu,
+                ProcessTableSortOrder::Descending
+            )
+            .is_none());
+    }
+}
@@ -0,0 +1,820 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! The sockets of the processes, found through the links in `/proc/<pid>/fd` on Linux and
+//! described by `/proc/net/tcp`, `tcp6`, `udp`, `udp6` and `unix`, and the bytes each TCP
+//! socket received and sent, asked of the kernel with sock_diag netlink messages. A process is
+//! credited with the traffic of the sockets it holds. One shared with its children only counts
+//! for the lowest pid holding it, usually their parent.
+
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::fmt;
+#[cfg(target_os = "linux")]
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(target_os = "linux")]
+use std::path::Path;
+
+/// Length of a netlink message header.
+const NLMSG_HDR_LEN: usize = 16;
+/// Length of `inet_diag_req_v2`.
+const INET_DIAG_REQ_LEN: usize = 56;
+/// Length of `inet_diag_msg`, which the attributes of a socket follow.
+const INET_DIAG_MSG_LEN: usize = 72;
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+/// Attribute holding the `tcp_info` of a socket.
+const INET_DIAG_INFO: u16 = 2;
+/// Offsets of `tcpi_bytes_acked` and `tcpi_bytes_received` in `tcp_info`, since Linux 4.1.
+const TCPI_BYTES_ACKED: usize = 120;
+const TCPI_BYTES_RECEIVED: usize = 128;
+
+/// `__SO_ACCEPTCON` in the flags of `/proc/net/unix`, set on listening sockets.
+const UNIX_ACCEPTCON: u32 = 0x10000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+    UnixStream,
+    UnixDatagram,
+    UnixSeqPacket,
+}
+
+impl fmt::Display for Protocol {
+    /// As `ss` names them.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+            Protocol::UnixStream => "u_str",
+            Protocol::UnixDatagram => "u_dgr",
+            Protocol::UnixSeqPacket => "u_seq",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SocketState {
+    Listen,
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    CloseWait,
+    LastAck,
+    Closing,
+    /// Closed, or a UDP or Unix socket without a peer.
+    Unconnected,
+    Unknown,
+}
+
+impl SocketState {
+    /// The state in `/proc/net/tcp` and `udp`, as in `include/net/tcp_states.h`.
+    fn from_inet(code: u8) -> SocketState {
+        match code {
+            0x01 => SocketState::Established,
+            0x02 => SocketState::SynSent,
+            0x03 => SocketState::SynRecv,
+            0x04 => SocketState::FinWait1,
+            0x05 => SocketState::FinWait2,
+            0x06 => SocketState::TimeWait,
+            0x07 => SocketState::Unconnected,
+            0x08 => SocketState::CloseWait,
+            0x09 => SocketState::LastAck,
+            0x0A => SocketState::Listen,
+            0x0B => SocketState::Closing,
+            _ => SocketState::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for SocketState {
+    /// As `ss` names them.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SocketState::Listen => "LISTEN",
+            SocketState::Established => "ESTAB",
+            SocketState::SynSent => "SYN-SENT",
+            SocketState::SynRecv => "SYN-RECV",
+            SocketState::FinWait1 => "FIN-WAIT-1",
+            SocketState::FinWait2 => "FIN-WAIT-2",
+            SocketState::TimeWait => "TIME-WAIT",
+            SocketState::CloseWait => "CLOSE-WAIT",
+            SocketState::LastAck => "LAST-ACK",
+            SocketState::Closing => "CLOSING",
+            SocketState::Unconnected => "UNCONN",
+            SocketState::Unknown => "?",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Address {
+    Inet(SocketAddr),
+    /// Path of a Unix socket, abstract ones start with `@`, empty if it has none.
+    Unix(String),
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Address::Inet(addr) if addr.port() == 0 => match addr.ip() {
+                IpAddr::V4(ip) => write!(f, "{}:*", ip),
+                IpAddr::V6(ip) => write!(f, "[{}]:*", ip),
+            },
+            Address::Inet(addr) => write!(f, "{}", addr),
+            Address::Unix(path) if path.is_empty() => write!(f, "*"),
+            Address::Unix(path) => write!(f, "{}", path),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Socket {
+    pub inode: u64,
+    pub protocol: Protocol,
+    pub state: SocketState,
+    pub local: Address,
+    /// None for Unix sockets, whose peer isn't listed.
+    pub remote: Option<Address>,
+    /// Bytes waiting to be read by the process, or for a listening TCP socket the connections
+    /// waiting to be accepted. None for Unix sockets.
+    pub recv_queue: Option<u32>,
+    /// Bytes not yet sent or acknowledged by the peer.
+    pub send_queue: Option<u32>,
+}
+
+/// An address and port in `/proc/net/tcp` or `tcp6`: the words of the address as hexadecimal
+/// numbers in the byte order of the machine, then the port, e.g. `0100007F:1F90`.
+fn parse_inet_address(s: &str) -> Option<SocketAddr> {
+    let (ip, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let word = |i: usize| -> Option<[u8; 4]> {
+        Some(
+            u32::from_str_radix(ip.get(i * 8..i * 8 + 8)?, 16)
+                .ok()?
+                .to_ne_bytes(),
+        )
+    };
+    let ip = match ip.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(word(0)?)),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                chunk.copy_from_slice(&word(i)?);
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Sockets in the contents of `/proc/net/tcp`, `tcp6`, `udp` or `udp6`.
+pub fn parse_inet_sockets(s: &str, protocol: Protocol) -> Vec<Socket> {
+    s.lines()
+        .skip(1)
+        .filter_map(|line| {
+            // sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid
+            // timeout inode ...
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (send_queue, recv_queue) = fields.get(4)?.split_once(':')?;
+            Some(Socket {
+                inode: fields.get(9)?.parse().ok()?,
+                protocol,
+                state: SocketState::from_inet(u8::from_str_radix(fields.get(3)?, 16).ok()?),
+                local: Address::Inet(parse_inet_address(fields.get(1)?)?),
+                remote: Some(Address::Inet(parse_inet_address(fields.get(2)?)?)),
+                recv_queue: Some(u32::from_str_radix(recv_queue, 16).ok()?),
+                send_queue: Some(u32::from_str_radix(send_queue, 16).ok()?),
+            })
+        })
+        .collect()
+}
+
+/// Sockets in the contents of `/proc/net/unix`.
+pub fn parse_unix_sockets(s: &str) -> Vec<Socket> {
+    s.lines()
+        .skip(1)
+        .filter_map(|line| {
+            // Num RefCount Protocol Flags Type St Inode Path
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let flags = u32::from_str_radix(fields.get(3)?, 16).ok()?;
+            let protocol = match *fields.get(4)? {
+                "0001" => Protocol::UnixStream,
+                "0002" => Protocol::UnixDatagram,
+                "0005" => Protocol::UnixSeqPacket,
+                _ => return None,
+            };
+            let state = if flags & UNIX_ACCEPTCON != 0 {
+                SocketState::Listen
+            } else {
+                match *fields.get(5)? {
+                    "01" => SocketState::Unconnected,
+                    "02" => SocketState::SynSent,
+                    "03" => SocketState::Established,
+                    "04" => SocketState::Closing,
+                    _ => SocketState::Unknown,
+                }
+            };
+            Some(Socket {
+                inode: fields.get(6)?.parse().ok()?,
+                protocol,
+                state,
+                local: Address::Unix(fields.get(7..).unwrap_or_default().join(" ")),
+                remote: None,
+                recv_queue: None,
+                send_queue: None,
+            })
+        })
+        .collect()
+}
+
+/// TCP and UDP sockets of the network namespace whose `/proc/net` directory is `net`.
+#[cfg(target_os = "linux")]
+fn read_inet_sockets(net: &Path) -> Vec<Socket> {
+    let mut sockets = vec![];
+    for (file, protocol) in [
+        ("tcp", Protocol::Tcp),
+        ("tcp6", Protocol::Tcp),
+        ("udp", Protocol::Udp),
+        ("udp6", Protocol::Udp),
+    ] {
+        let contents = std::fs::read_to_string(net.join(file)).unwrap_or_default();
+        sockets.extend(parse_inet_sockets(&contents, protocol));
+    }
+    sockets
+}
+
+/// Sockets of the network namespace whose `/proc/net` directory is `net`.
+#[cfg(target_os = "linux")]
+pub fn read_sockets(net: &Path) -> Vec<Socket> {
+    let mut sockets = read_inet_sockets(net);
+    let unix = std::fs::read_to_string(net.join("unix")).unwrap_or_default();
+    sockets.extend(parse_unix_sockets(&unix));
+    sockets
+}
+
+/// Sockets `pid` has open, by protocol, state and local address.
+#[cfg(target_os = "linux")]
+pub fn process_sockets(pid: u32) -> io::Result<Vec<Socket>> {
+    let inodes: HashSet<u64> = socket_inodes(pid)?.into_iter().collect();
+    if inodes.is_empty() {
+        return Ok(vec![]);
+    }
+    // the process may be in a network namespace of its own
+    let mut sockets: Vec<Socket> = read_sockets(Path::new(&format!("/proc/{pid}/net")))
+        .into_iter()
+        .filter(|s| inodes.contains(&s.inode))
+        .collect();
+    sockets.sort_by(|a, b| (a.protocol, a.state, &a.local).cmp(&(b.protocol, b.state, &b.local)));
+    Ok(sockets)
+}
+
+/// A TCP socket listening for connections or a UDP socket bound to a port, and the processes
+/// holding it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListeningPort {
+    pub protocol: Protocol,
+    pub local: SocketAddr,
+    /// Lowest first, none if the holders couldn't be seen.
+    pub pids: Vec<u32>,
+}
+
+impl Socket {
+    /// The address it listens on, if it is a listening TCP or bound UDP socket.
+    fn listening_on(&self) -> Option<SocketAddr> {
+        match (&self.local, self.protocol, self.state) {
+            (Address::Inet(addr), Protocol::Tcp, SocketState::Listen)
+            | (Address::Inet(addr), Protocol::Udp, SocketState::Unconnected)
+                if addr.port() != 0 =>
+            {
+                Some(*addr)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The listening sockets among `sockets` by port, held by the pids in `holders` by inode.
+pub fn listening_ports(sockets: &[Socket], holders: &HashMap<u64, Vec<u32>>) -> Vec<ListeningPort> {
+    let mut ports: Vec<ListeningPort> = sockets
+        .iter()
+        .filter_map(|s| {
+            let mut pids = holders.get(&s.inode).cloned().unwrap_or_default();
+            pids.sort_unstable();
+            Some(ListeningPort {
+                protocol: s.protocol,
+                local: s.listening_on()?,
+                pids,
+            })
+        })
+        .collect();
+    ports.sort_by(|a, b| {
+        (a.local.port(), a.protocol, a.local).cmp(&(b.local.port(), b.protocol, b.local))
+    });
+    ports
+}
+
+/// The listening sockets of the network namespace zenith runs in and which of `pids` hold
+/// them.
+#[cfg(target_os = "linux")]
+pub fn read_listening_ports(pids: impl Iterator<Item = u32>) -> Vec<ListeningPort> {
+    let sockets: Vec<Socket> = read_inet_sockets(Path::new("/proc/net"))
+        .into_iter()
+        .filter(|s| s.listening_on().is_some())
+        .collect();
+    let inodes: HashSet<u64> = sockets.iter().map(|s| s.inode).collect();
+    let mut holders: HashMap<u64, Vec<u32>> = HashMap::new();
+    for pid in pids {
+        for inode in socket_inodes(pid).unwrap_or_default() {
+            if inodes.contains(&inode) {
+                holders.entry(inode).or_default().push(pid);
+            }
+        }
+    }
+    listening_ports(&sockets, &holders)
+}
+
+/// Bytes received and sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SocketBytes {
+    pub rx: u64,
+    pub tx: u64,
+}
+
+impl SocketBytes {
+    fn since(&self, before: &SocketBytes) -> SocketBytes {
+        SocketBytes {
+            rx: self.rx.saturating_sub(before.rx),
+            tx: self.tx.saturating_sub(before.tx),
+        }
+    }
+
+    fn add(&self, other: &SocketBytes) -> SocketBytes {
+        SocketBytes {
+            rx: self.rx + other.rx,
+            tx: self.tx + other.tx,
+        }
+    }
+}
+
+/// The inode of a socket in the target of a `/proc/<pid>/fd` link, `socket:[12345]`.
+pub fn parse_socket_link(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Inodes of the sockets `pid` has open.
+#[cfg(target_os = "linux")]
+pub fn socket_inodes(pid: u32) -> io::Result<Vec<u64>> {
+    Ok(std::fs::read_dir(format!("/proc/{pid}/fd"))?
+        .filter_map(|fd| {
+            let link = std::fs::read_link(fd.ok()?.path()).ok()?;
+            parse_socket_link(link.to_str()?)
+        })
+        .collect())
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(
+        buf.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        buf.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(
+        buf.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// Netlink messages and attributes start on 4 byte boundaries.
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+/// Request for every TCP socket of `family` along with its `tcp_info`.
+#[cfg(target_os = "linux")]
+fn diag_request(family: u8) -> Vec<u8> {
+    let len = NLMSG_HDR_LEN + INET_DIAG_REQ_LEN;
+    let mut request = Vec::with_capacity(len);
+    request.extend((len as u32).to_ne_bytes());
+    request.extend(SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    request.extend(((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+    // sequence number and port id, the kernel's
+    request.extend([0; 8]);
+    // family, protocol, extensions wanted and padding
+    request.extend([
+        family,
+        libc::IPPROTO_TCP as u8,
+        1 << (INET_DIAG_INFO - 1),
+        0,
+    ]);
+    // sockets in any state, with any address
+    request.extend(u32::MAX.to_ne_bytes());
+    request.resize(len, 0);
+    request
+}
+
+/// Inode and bytes of the socket in an `inet_diag_msg` and its attributes.
+fn parse_diag_msg(msg: &[u8]) -> Option<(u64, SocketBytes)> {
+    let inode = u32_at(msg, 68)? as u64;
+    let mut attrs = msg.get(INET_DIAG_MSG_LEN..)?;
+    while let (Some(len), Some(kind)) = (u16_at(attrs, 0), u16_at(attrs, 2)) {
+        let len = len as usize;
+        if len < 4 || len > attrs.len() {
+            break;
+        }
+        if kind == INET_DIAG_INFO {
+            let info = &attrs[4..len];
+            let bytes = SocketBytes {
+                rx: u64_at(info, TCPI_BYTES_RECEIVED)?,
+                tx: u64_at(info, TCPI_BYTES_ACKED)?,
+            };
+            // sockets in time wait belong to no one
+            return (inode != 0).then_some((inode, bytes));
+        }
+        attrs = &attrs[align(len).min(attrs.len())..];
+    }
+    None
+}
+
+/// Adds the sockets in a buffer of sock_diag replies to `sockets`, returning whether it held
+/// the end of the dump.
+#[cfg(target_os = "linux")]
+fn parse_diag_replies(buf: &[u8], sockets: &mut HashMap<u64, SocketBytes>) -> io::Result<bool> {
+    let mut rest = buf;
+    while let (Some(len), Some(kind)) = (u32_at(rest, 0), u16_at(rest, 4)) {
+        let len = len as usize;
+        if len < NLMSG_HDR_LEN || len > rest.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "truncated sock_diag reply",
+            ));
+        }
+        match kind {
+            NLMSG_DONE => return Ok(true),
+            NLMSG_ERROR => {
+                let errno = u32_at(rest, NLMSG_HDR_LEN).unwrap_or(0) as i32;
+                return Err(io::Error::from_raw_os_error(-errno));
+            }
+            SOCK_DIAG_BY_FAMILY => {
+                if let Some((inode, bytes)) = parse_diag_msg(&rest[NLMSG_HDR_LEN..len]) {
+                    sockets.insert(inode, bytes);
+                }
+            }
+            _ => {}
+        }
+        rest = &rest[align(len).min(rest.len())..];
+    }
+    Ok(false)
+}
+
+/// Bytes received and sent by each TCP socket since it was opened, by inode.
+#[cfg(target_os = "linux")]
+fn tcp_socket_bytes() -> io::Result<HashMap<u64, SocketBytes>> {
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+            libc::NETLINK_SOCK_DIAG,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // closed when dropped
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+    let mut sockets = HashMap::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        let request = diag_request(family as u8);
+        let sent = unsafe {
+            libc::send(
+                socket.as_raw_fd(),
+                request.as_ptr() as *const libc::c_void,
+                request.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        loop {
+            let n = unsafe {
+                libc::recv(
+                    socket.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    0,
+                )
+            };
+            if n < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if n == 0 || parse_diag_replies(&buf[..n as usize], &mut sockets)? {
+                break;
+            }
+        }
+    }
+    Ok(sockets)
+}
+
+/// The bytes of each TCP socket as last read, to tell what the processes received and sent
+/// during a tick.
+#[derive(Default)]
+pub struct SocketTraffic {
+    /// None before the first reading.
+    sockets: Option<HashMap<u64, SocketBytes>>,
+    /// Process holding each socket that moved data, None if no process we can read holds it.
+    owners: HashMap<u64, Option<u32>>,
+}
+
+impl SocketTraffic {
+    /// Bytes the sockets of each of `pids` received and sent since the last reading, leaving
+    /// out the processes that had no traffic.
+    /// A socket shared by several processes, as by pre-forking servers, counts for the lowest pid
+    /// holding it.
+    #[allow(unused_variables)]
+    pub fn read(&mut self, pids: impl Iterator<Item = u32>) -> HashMap<u32, SocketBytes> {
+        #[cfg(target_os = "linux")]
+        {
+            let sockets = match tcp_socket_bytes() {
+                Ok(sockets) => sockets,
+                Err(e) => {
+                    debug!("Couldn't read the TCP sockets: {}", e);
+                    return HashMap::new();
+                }
+            };
+            let grown = self.update(sockets);
+            if grown.is_empty() {
+                return HashMap::new();
+            }
+            let mut pids: Vec<u32> = pids.collect();
+            pids.sort_unstable();
+            self.find_owners(&grown, &pids);
+            let mut traffic: HashMap<u32, SocketBytes> = HashMap::new();
+            for (inode, bytes) in grown.iter() {
+                if let Some(Some(pid)) = self.owners.get(inode) {
+                    let total = traffic.entry(*pid).or_default();
+                    *total = total.add(bytes);
+                }
+            }
+            traffic
+        }
+        #[cfg(not(target_os = "linux"))]
+        HashMap::new()
+    }
+
+    /// Finds the processes among the sorted `pids` holding the sockets in `grown` not mapped
+    /// yet. Only then are the descriptors of the processes read, lowest pid first and up to the
+    /// last one it takes.
+    #[cfg(target_os = "linux")]
+    fn find_owners(&mut self, grown: &HashMap<u64, SocketBytes>, pids: &[u32]) {
+        // the socket outlived its process or was handed to another one
+        self.owners
+            .retain(|_, pid| pid.is_none_or(|pid| pids.binary_search(&pid).is_ok()));
+        let mut unmapped: HashSet<u64> = grown
+            .keys()
+            .filter(|inode| !self.owners.contains_key(inode))
+            .copied()
+            .collect();
+        for pid in pids {
+            if unmapped.is_empty() {
+                return;
+            }
+            // those of other users can't be read without root
+            for inode in socket_inodes(*pid).unwrap_or_default() {
+                if unmapped.remove(&inode) {
+                    self.owners.insert(inode, Some(*pid));
+                }
+            }
+        }
+        self.owners
+            .extend(unmapped.into_iter().map(|inode| (inode, None)));
+    }
+
+    /// Takes the bytes of each socket just read, returning how much they grew for the sockets
+    /// that moved data. Sockets opened since the last reading count from nothing, the first
+    /// reading is only where to start from.
+    fn update(&mut self, sockets: HashMap<u64, SocketBytes>) -> HashMap<u64, SocketBytes> {
+        let grown = match &self.sockets {
+            Some(before) => sockets
+                .iter()
+                .map(|(inode, now)| {
+                    let before = before.get(inode).copied().unwrap_or_default();
+                    (*inode, now.since(&before))
+                })
+                .filter(|(_, grown)| *grown != SocketBytes::default())
+                .collect(),
+            None => HashMap::new(),
+        };
+        self.owners.retain(|inode, _| sockets.contains_key(inode));
+        self.sockets = Some(sockets);
+        grown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(rx: u64, tx: u64) -> SocketBytes {
+        SocketBytes { rx, tx }
+    }
+
+    /// An `inet_diag_msg` of the socket `inode` followed by its `tcp_info`.
+    fn diag_msg(inode: u32, rx: u64, tx: u64) -> Vec<u8> {
+        let mut msg = vec![0u8; INET_DIAG_MSG_LEN];
+        msg[68..72].copy_from_slice(&inode.to_ne_bytes());
+        // an attribute of another kind first
+        msg.extend(8u16.to_ne_bytes());
+        msg.extend(1u16.to_ne_bytes());
+        msg.extend([0; 4]);
+        let mut info = vec![0u8; 232];
+        info[TCPI_BYTES_ACKED..TCPI_BYTES_ACKED + 8].copy_from_slice(&tx.to_ne_bytes());
+        info[TCPI_BYTES_RECEIVED..TCPI_BYTES_RECEIVED + 8].copy_from_slice(&rx.to_ne_bytes());
+        msg.extend((info.len() as u16 + 4).to_ne_bytes());
+        msg.extend(INET_DIAG_INFO.to_ne_bytes());
+        msg.extend(info);
+        msg
+    }
+
+    #[test]
+    fn test_parse_socket_link() {
+        assert_eq!(parse_socket_link("socket:[48213]"), Some(48213));
+        assert_eq!(parse_socket_link("pipe:[48213]"), None);
+        assert_eq!(parse_socket_link("/dev/null"), None);
+    }
+
+    #[test]
+    fn test_parse_diag_msg() {
+        assert_eq!(
+            parse_diag_msg(&diag_msg(48213, 1000, 20)),
+            Some((48213, bytes(1000, 20)))
+        );
+        assert_eq!(parse_diag_msg(&diag_msg(0, 1000, 20)), None);
+        // without tcp_info
+        assert_eq!(
+            parse_diag_msg(&diag_msg(48213, 1000, 20)[..INET_DIAG_MSG_LEN + 8]),
+            None
+        );
+        assert_eq!(parse_diag_msg(&[0; 16]), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_diag_replies() {
+        let reply = |kind: u16, payload: &[u8]| {
+            let mut msg = ((NLMSG_HDR_LEN + payload.len()) as u32)
+                .to_ne_bytes()
+                .to_vec();
+            msg.extend(kind.to_ne_bytes());
+            msg.extend([0; 10]);
+            msg.extend(payload);
+            msg.resize(align(msg.len()), 0);
+            msg
+        };
+        let mut buf = reply(SOCK_DIAG_BY_FAMILY, &diag_msg(1, 10, 20));
+        buf.extend(reply(SOCK_DIAG_BY_FAMILY, &diag_msg(2, 30, 40)));
+        let mut sockets = HashMap::new();
+        assert!(!parse_diag_replies(&buf, &mut sockets).unwrap());
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[&2], bytes(30, 40));
+
+        assert!(parse_diag_replies(&reply(NLMSG_DONE, &[0; 4]), &mut sockets).unwrap());
+        let error = reply(NLMSG_ERROR, &(-libc::EINVAL).to_ne_bytes());
+        assert_eq!(
+            parse_diag_replies(&error, &mut sockets)
+                .unwrap_err()
+                .raw_os_error(),
+            Some(libc::EINVAL)
+        );
+        assert!(parse_diag_replies(&buf[..40], &mut sockets).is_err());
+    }
+
+    // as printed on little endian machines
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_inet_sockets() {
+        let tcp = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:1F90 00000000:0000 0A 00000000:00000003 00:00000000 00000000  1000        0 48213 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:D4A2 01 00000010:00000000 01:00000016 00000000  1000        0 48290 2 0000000000000000 20 4 30 10 -1
+";
+        let sockets = parse_inet_sockets(tcp, Protocol::Tcp);
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].inode, 48213);
+        assert_eq!(sockets[0].state, SocketState::Listen);
+        assert_eq!(sockets[0].local.to_string(), "0.0.0.0:8080");
+        assert_eq!(sockets[0].remote.as_ref().unwrap().to_string(), "0.0.0.0:*");
+        assert_eq!(sockets[0].recv_queue, Some(3));
+        assert_eq!(sockets[1].state, SocketState::Established);
+        assert_eq!(
+            sockets[1].remote.as_ref().unwrap().to_string(),
+            "127.0.0.1:54434"
+        );
+        assert_eq!(sockets[1].send_queue, Some(16));
+
+        let udp6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  101: 00000000000000000000000001000000:0035 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 20170 2 0000000000000000 0
+";
+        let sockets = parse_inet_sockets(udp6, Protocol::Udp);
+        assert_eq!(sockets[0].state, SocketState::Unconnected);
+        assert_eq!(sockets[0].local.to_string(), "[::1]:53");
+        assert_eq!(sockets[0].protocol.to_string(), "udp");
+    }
+
+    #[test]
+    fn test_parse_unix_sockets() {
+        let unix = "\
+Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 18342 /run/systemd/private
+0000000000000000: 00000003 00000000 00000000 0001 03 48311
+0000000000000000: 00000002 00000000 00000000 0002 01 17001 @/org/kernel/udev
+";
+        let sockets = parse_unix_sockets(unix);
+        assert_eq!(sockets.len(), 3);
+        assert_eq!(sockets[0].state, SocketState::Listen);
+        assert_eq!(sockets[0].local.to_string(), "/run/systemd/private");
+        assert_eq!(sockets[1].state, SocketState::Established);
+        assert_eq!(sockets[1].local.to_string(), "*");
+        assert_eq!(sockets[1].remote, None);
+        assert_eq!(sockets[2].protocol, Protocol::UnixDatagram);
+        assert_eq!(sockets[2].inode, 17001);
+    }
+
+    #[test]
+    fn test_listening_ports() {
+        let socket = |inode, protocol, state, local: &str| Socket {
+            inode,
+            protocol,
+            state,
+            local: Address::Inet(local.parse().unwrap()),
+            remote: None,
+            recv_queue: Some(0),
+            send_queue: Some(0),
+        };
+        let sockets = vec![
+            socket(1, Protocol::Tcp, SocketState::Listen, "0.0.0.0:8080"),
+            socket(2, Protocol::Tcp, SocketState::Established, "10.0.0.5:8080"),
+            socket(3, Protocol::Udp, SocketState::Unconnected, "127.0.0.53:53"),
+            socket(4, Protocol::Tcp, SocketState::Listen, "[::]:22"),
+            // a UDP socket connected to its peer, or not bound yet
+            socket(5, Protocol::Udp, SocketState::Established, "10.0.0.5:41234"),
+            socket(6, Protocol::Udp, SocketState::Unconnected, "0.0.0.0:0"),
+            socket(7, Protocol::Tcp, SocketState::Listen, "127.0.0.1:8080"),
+        ];
+        let holders = HashMap::from([(1, vec![812, 17]), (3, vec![402])]);
+        let ports = listening_ports(&sockets, &holders);
+        let listed: Vec<(String, Vec<u32>)> = ports
+            .iter()
+            .map(|p| (format!("{} {}", p.protocol, p.local), p.pids.clone()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("tcp [::]:22".to_string(), vec![]),
+                ("udp 127.0.0.53:53".to_string(), vec![402]),
+                ("tcp 0.0.0.0:8080".to_string(), vec![17, 812]),
+                ("tcp 127.0.0.1:8080".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_socket_traffic_update() {
+        let mut traffic = SocketTraffic::default();
+        assert!(traffic
+            .update(HashMap::from([(1, bytes(100, 100))]))
+            .is_empty());
+        let grown = traffic.update(HashMap::from([(1, bytes(150, 100)), (2, bytes(10, 5))]));
+        assert_eq!(grown, HashMap::from([(1, bytes(50, 0)), (2, bytes(10, 5))]));
+        // closed sockets are forgotten, with their process
+        traffic.owners = HashMap::from([(1, Some(7)), (2, None)]);
+        let grown = traffic.update(HashMap::from([(2, bytes(10, 5))]));
+        assert!(grown.is_empty());
+        assert_eq!(traffic.owners, HashMap::from([(2, None)]));
+    }
+}
@@ -0,0 +1,247 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! The processes using the most of each resource, kept for every tick of the raw history so a
+//! spike can be attributed when scrolling back.
+
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Processes kept per resource and tick.
+pub const TOP_CONSUMERS: usize = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TopMetric {
+    /// CPU usage in percent of one core.
+    Cpu,
+    /// Resident memory in bytes.
+    Mem,
+    /// Disk reads in bytes per second.
+    Read,
+    /// Disk writes in bytes per second.
+    Write,
+    /// Time spent waiting on I/O in percent.
+    IoWait,
+    /// TCP bytes received per second.
+    NetRx,
+    /// TCP bytes sent per second.
+    NetTx,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Consumer {
+    pub metric: TopMetric,
+    pub pid: u32,
+    pub name: String,
+    pub value: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct Entry {
+    metric: TopMetric,
+    pid: u32,
+    /// Index into the name table.
+    name: u32,
+    value: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TopHistory {
+    /// Process names, each stored once.
+    names: Vec<String>,
+    /// Oldest first, aligned with the newest sample times.
+    ticks: VecDeque<Vec<Entry>>,
+    #[serde(skip)]
+    index: HashMap<String, u32>,
+}
+
+impl TopHistory {
+    /// Starts a tick with no consumers recorded.
+    pub fn push_tick(&mut self) {
+        self.ticks.push_back(vec![]);
+    }
+
+    /// Replaces the consumers of the newest tick.
+    pub fn set_last(&mut self, consumers: &[Consumer]) {
+        let entries = consumers
+            .iter()
+            .map(|c| Entry {
+                metric: c.metric,
+                pid: c.pid,
+                name: self.intern(&c.name),
+                value: c.value,
+            })
+            .collect();
+        if let Some(last) = self.ticks.back_mut() {
+            *last = entries;
+        }
+    }
+
+    fn intern(&mut self, name: &str) -> u32 {
+        if self.index.len() != self.names.len() {
+            // not serialized, rebuilt after loading
+            self.index = self
+                .names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| (n.clone(), i as u32))
+                .collect();
+        }
+        if let Some(i) = self.index.get(name) {
+            return *i;
+        }
+        let i = self.names.len() as u32;
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), i);
+        i
+    }
+
+    /// Drops the oldest ticks so at most `keep` are left.
+    pub fn truncate_front(&mut self, keep: usize) {
+        if self.ticks.len() <= keep {
+            return;
+        }
+        self.ticks.drain(..self.ticks.len() - keep);
+        if self.names.len() >= 1024 {
+            self.collect_names();
+        }
+    }
+
+    /// Removes the names no tick refers to anymore.
+    fn collect_names(&mut self) {
+        let mut used = vec![false; self.names.len()];
+        for e in self.ticks.iter().flatten() {
+            used[e.name as usize] = true;
+        }
+        if used.iter().filter(|u| **u).count() * 2 > self.names.len() {
+            return;
+        }
+        let mut renumber = vec![0u32; self.names.len()];
+        let mut names = vec![];
+        for (i, name) in self.names.drain(..).enumerate() {
+            if used[i] {
+                renumber[i] = names.len() as u32;
+                names.push(name);
+            }
+        }
+        for e in self.ticks.iter_mut().flatten() {
+            e.name = renumber[e.name as usize];
+        }
+        self.names = names;
+        self.index.clear();
+    }
+
+    pub fn clear(&mut self) {
+        *self = TopHistory::default();
+    }
+
+    /// The top consumers of each resource during the `ticks` ticks starting `age` ticks before
+    /// the newest one, by their peak in that time. Empty if nothing was recorded then.
+    pub fn at(&self, age: usize, ticks: usize) -> Vec<Consumer> {
+        let len = self.ticks.len();
+        let end = len.saturating_sub(age);
+        let start = end.saturating_sub(ticks.max(1));
+        let mut peaks: HashMap<(TopMetric, u32, u32), f32> = HashMap::new();
+        for e in self.ticks.range(start..end).flatten() {
+            let peak = peaks.entry((e.metric, e.pid, e.name)).or_insert(e.value);
+            *peak = peak.max(e.value);
+        }
+        let mut consumers: Vec<Consumer> = peaks
+            .into_iter()
+            .map(|((metric, pid, name), value)| Consumer {
+                metric,
+                pid,
+                name: self.names[name as usize].clone(),
+                value,
+            })
+            .collect();
+        consumers.sort_by(|a, b| {
+            a.metric
+                .cmp(&b.metric)
+                .then(b.value.total_cmp(&a.value))
+                .then(a.pid.cmp(&b.pid))
+        });
+        let mut kept: HashMap<TopMetric, usize> = HashMap::new();
+        consumers.retain(|c| {
+            let n = kept.entry(c.metric).or_default();
+            *n += 1;
+            *n <= TOP_CONSUMERS
+        });
+        consumers
+    }
+}
+
+/// The consumers of `metric` among those returned by `TopHistory::at`, highest first.
+pub fn consumers_of(consumers: &[Consumer], metric: TopMetric) -> impl Iterator<Item = &Consumer> {
+    consumers.iter().filter(move |c| c.metric == metric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consumer(metric: TopMetric, pid: u32, name: &str, value: f32) -> Consumer {
+        Consumer {
+            metric,
+            pid,
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_top_history_at() {
+        let mut top = TopHistory::default();
+        top.push_tick();
+        top.set_last(&[
+            consumer(TopMetric::Cpu, 1, "make", 10.0),
+            consumer(TopMetric::Read, 2, "tar", 4096.0),
+        ]);
+        top.push_tick();
+        top.set_last(&[
+            consumer(TopMetric::Cpu, 3, "rustc", 90.0),
+            consumer(TopMetric::Cpu, 1, "make", 20.0),
+        ]);
+        top.push_tick();
+
+        assert!(top.at(0, 1).is_empty());
+        assert_eq!(
+            top.at(1, 1),
+            vec![
+                consumer(TopMetric::Cpu, 3, "rustc", 90.0),
+                consumer(TopMetric::Cpu, 1, "make", 20.0),
+            ]
+        );
+        // a column of several ticks shows each process at its peak
+        assert_eq!(
+            top.at(0, 3),
+            vec![
+                consumer(TopMetric::Cpu, 3, "rustc", 90.0),
+                consumer(TopMetric::Cpu, 1, "make", 20.0),
+                consumer(TopMetric::Read, 2, "tar", 4096.0),
+            ]
+        );
+        assert!(top.at(3, 1).is_empty());
+    }
+
+    #[test]
+    fn test_top_history_truncate() {
+        let mut top = TopHistory::default();
+        for i in 0..2000u32 {
+            top.push_tick();
+            top.set_last(&[consumer(TopMetric::Mem, i, &format!("p{}", i), 1.0)]);
+        }
+        top.truncate_front(10);
+        assert_eq!(top.ticks.len(), 10);
+        assert_eq!(top.names.len(), 10);
+        assert_eq!(
+            top.at(0, 1),
+            vec![consumer(TopMetric::Mem, 1999, "p1999", 1.0)]
+        );
+        // names are reused after they were renumbered
+        top.push_tick();
+        top.set_last(&[consumer(TopMetric::Mem, 7, "p1995", 2.0)]);
+        assert_eq!(top.names.len(), 10);
+    }
+}
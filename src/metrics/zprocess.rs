@@ -26,6 +26,8 @@ use sysinfo::ProcessStatus;
 use chrono::prelude::DateTime;
 use chrono::Duration as CDuration;
 use chrono::Local;
+use serde::{Deserialize as _, Deserializer, Serializer};
+use serde_derive::{Deserialize, Serialize};
 
 macro_rules! convert_result_to_string {
     ($x:expr) => {
@@ -125,7 +127,7 @@ fn get_priority(pid: u32) -> i32 {
 }
 
 #[allow(dead_code)]
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ZProcess {
     pub pid: u32,
     pub uid: u32,
@@ -135,6 +137,7 @@ pub struct ZProcess {
     pub cum_cpu_usage: f64,
     pub command: Vec<String>,
     pub exe: String,
+    #[serde(with = "status_code")]
     pub status: ProcessStatus,
     pub name: String,
     pub priority: i32,
@@ -163,6 +166,11 @@ pub struct ZProcess {
     pub peak_write_bytes_sec: f64,
     pub peak_gpu_usage: u64,
     pub peak_fb_utilization: u64,
+    /// Bytes the sockets of the process received and sent during the last tick.
+    pub net_rx_bytes: u64,
+    pub net_tx_bytes: u64,
+    pub peak_net_rx_bytes_sec: f64,
+    pub peak_net_tx_bytes_sec: f64,
 }
 
 #[cfg(target_os = "macos")]
@@ -240,6 +248,10 @@ impl ZProcess {
             peak_write_bytes_sec: 0.0,
             peak_gpu_usage: 0,
             peak_fb_utilization: 0,
+            net_rx_bytes: 0,
+            net_tx_bytes: 0,
+            peak_net_rx_bytes_sec: 0.0,
+            peak_net_tx_bytes_sec: 0.0,
         };
         set_addl_task_info(&mut zp);
 
@@ -259,6 +271,12 @@ impl ZProcess {
         );
         (self.write_bytes - self.prev_write_bytes) as f64 / tick_rate.as_secs_f64()
     }
+    pub fn get_net_rx_bytes_sec(&self, tick_rate: &Duration) -> f64 {
+        self.net_rx_bytes as f64 / tick_rate.as_secs_f64()
+    }
+    pub fn get_net_tx_bytes_sec(&self, tick_rate: &Duration) -> f64 {
+        self.net_tx_bytes as f64 / tick_rate.as_secs_f64()
+    }
 
     pub async fn suspend(&self) -> String {
         let pid_i32: i32 = match self.pid.try_into() {
@@ -507,6 +525,8 @@ impl ZProcess {
             ProcessTableSortBy::IOWait => |pa, pb, _tick| {
                 (pa.io_delay - pa.prev_io_delay).cmp(&(pb.io_delay - pb.prev_io_delay))
             },
+            ProcessTableSortBy::NetRx => |pa, pb, _tick| pa.net_rx_bytes.cmp(&pb.net_rx_bytes),
+            ProcessTableSortBy::NetTx => |pa, pb, _tick| pa.net_tx_bytes.cmp(&pb.net_tx_bytes),
         }
     }
 
@@ -545,6 +565,8 @@ impl ZProcess {
             ProcessTableSortBy::IOWait => |pa, pb, _tick| {
                 (pa.io_delay - pa.prev_io_delay).cmp(&(pb.io_delay - pb.prev_io_delay))
             },
+            ProcessTableSortBy::NetRx => |pa, pb, _tick| pa.net_rx_bytes.cmp(&pb.net_rx_bytes),
+            ProcessTableSortBy::NetTx => |pa, pb, _tick| pa.net_tx_bytes.cmp(&pb.net_tx_bytes),
             ProcessTableSortBy::Gpu => |pa, pb, _tick| pa.gpu_usage.cmp(&pb.gpu_usage),
             ProcessTableSortBy::FB => |pa, pb, _tick| pa.fb_utilization.cmp(&pb.fb_utilization),
         }
@@ -575,12 +597,39 @@ impl ProcessStatusExt for ProcessStatus {
     }
 }
 
+/// Serializes a `ProcessStatus` as its single char code, sysinfo's type isn't serializable.
+mod status_code {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(status: &ProcessStatus, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(status.to_single_char())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<ProcessStatus, D::Error> {
+        Ok(match String::deserialize(d)?.as_str() {
+            "I" => ProcessStatus::Idle,
+            "R" => ProcessStatus::Run,
+            "S" => ProcessStatus::Sleep,
+            "T" => ProcessStatus::Stop,
+            "Z" => ProcessStatus::Zombie,
+            "t" => ProcessStatus::Tracing,
+            "x" => ProcessStatus::Dead,
+            "K" => ProcessStatus::Wakekill,
+            "W" => ProcessStatus::Waking,
+            "P" => ProcessStatus::Parked,
+            "D" => ProcessStatus::UninterruptibleDiskSleep,
+            "L" => ProcessStatus::LockBlocked,
+            _ => ProcessStatus::Unknown(0),
+        })
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use std::time::Duration;
 
-    fn create_test_process() -> ZProcess {
+    pub(crate) fn create_test_process() -> ZProcess {
         ZProcess {
             pid: 1234,
             uid: 1000,
@@ -622,6 +671,10 @@ mod tests {
             peak_memory: 0,
             peak_read_bytes_sec: 0.0,
             peak_write_bytes_sec: 0.0,
+            net_rx_bytes: 0,
+            net_tx_bytes: 0,
+            peak_net_rx_bytes_sec: 0.0,
+            peak_net_tx_bytes_sec: 0.0,
         }
     }
 
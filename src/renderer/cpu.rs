@@ -4,17 +4,20 @@
 
 use super::style::{max_style, ok_style, MAX_COLOR, OK_COLOR};
 use crate::float_to_byte_string;
-use crate::metrics::histogram::{HistogramKind, View};
+use crate::metrics::histogram::{Histogram, HistogramKind, View};
+use crate::metrics::top::TopMetric;
 use crate::metrics::CPUTimeApp;
-use crate::renderer::{percent_of, split_left_right_pane, Render};
+use crate::renderer::{
+    history_sparkline, percent_of, render_cursor, split_left_right_pane, top_at_offset, Render,
+};
 use byte_unit::{Byte, Unit};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{BarChart, Block, Borders, Paragraph, Sparkline, Wrap};
+use ratatui::widgets::{BarChart, Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
 
-fn cpu_title<'a>(app: &'a CPUTimeApp, histogram: &'a [u64]) -> Line<'a> {
+fn cpu_title<'a>(app: &'a CPUTimeApp, histogram: &Histogram, view: &View) -> Line<'a> {
     let top_process_name = match &app.cum_cpu_process {
         Some(p) => p.name.as_str(),
         None => "",
@@ -27,15 +30,13 @@ fn cpu_title<'a>(app: &'a CPUTimeApp, histogram: &'a [u64]) -> Line<'a> {
         Some(p) => p.pid,
         None => 0,
     };
-    let mean: f64 = match histogram.len() {
+    let recorded: Vec<u64> = histogram.recorded().collect();
+    let mean: f64 = match recorded.len() {
         0 => 0.0,
-        _ => histogram.iter().sum::<u64>() as f64 / histogram.len() as f64,
+        _ => recorded.iter().sum::<u64>() as f64 / recorded.len() as f64,
     };
 
-    let peak: u64 = match histogram.len() {
-        0 => 0,
-        _ => histogram.iter().max().unwrap_or(&0).to_owned(),
-    };
+    let peak: u64 = recorded.iter().max().copied().unwrap_or(0);
     let temp = if !app.sensors.is_empty() {
         let t = app
             .sensors
@@ -87,22 +88,30 @@ fn cpu_title<'a>(app: &'a CPUTimeApp, histogram: &'a [u64]) -> Line<'a> {
             format!("{peak: >3.2}%",),
             if peak > 90 { max_style() } else { ok_style() },
         ),
-        Span::raw(format!(
-            "] TOP [{top_pid} - {top_process_name} - {top_process_amt}]"
-        )),
+        Span::raw("] "),
+        Span::raw(
+            top_at_offset(app, view, TopMetric::Cpu, |v| format!("{v:.0}%")).unwrap_or_else(|| {
+                format!("TOP [{top_pid} - {top_process_name} - {top_process_amt}]")
+            }),
+        ),
     ])
 }
 
-fn mem_title(app: &'_ CPUTimeApp) -> Line<'_> {
+fn mem_title<'a>(app: &'a CPUTimeApp, view: &View) -> Line<'a> {
     let mem = percent_of(app.mem_utilization, app.mem_total) as u64;
     let swp = percent_of(app.swap_utilization, app.swap_total) as u64;
 
-    let top_mem_proc = match app.top_pids.mem.pid {
-        Some(pid) => match app.process_map.get(&pid) {
-            Some(p) => format!("TOP [{:} - {:} - {:}]", p.pid, p.name, p.user_name),
+    let top_mem_proc = match top_at_offset(app, view, TopMetric::Mem, |v| {
+        float_to_byte_string!(v as f64, Unit::B)
+    }) {
+        Some(top) => top,
+        None => match app.top_pids.mem.pid {
+            Some(pid) => match app.process_map.get(&pid) {
+                Some(p) => format!("TOP [{:} - {:} - {:}]", p.pid, p.name, p.user_name),
+                None => String::from(""),
+            },
             None => String::from(""),
         },
-        None => String::from(""),
     };
 
     Line::from(vec![
@@ -136,13 +145,13 @@ fn render_cpu_histogram(app: &CPUTimeApp, area: Rect, f: &mut Frame<'_>, view: &
         Some(h) => h,
         None => return,
     };
-    let title = cpu_title(app, h.data());
-    Sparkline::default()
+    let title = cpu_title(app, &h, view);
+    history_sparkline(&h)
         .block(Block::default().title(title))
-        .data(h.data())
         .style(Style::default().fg(Color::Blue))
         .max(100)
         .render(f, area);
+    render_cursor(&h, area, f, view);
 }
 
 fn render_memory_histogram(app: &CPUTimeApp, area: Rect, f: &mut Frame<'_>, view: &View) {
@@ -150,13 +159,13 @@ fn render_memory_histogram(app: &CPUTimeApp, area: Rect, f: &mut Frame<'_>, view
         Some(h) => h,
         None => return,
     };
-    let title2 = mem_title(app);
-    Sparkline::default()
+    let title2 = mem_title(app, view);
+    history_sparkline(&h)
         .block(Block::default().title(title2))
-        .data(h.data())
         .style(Style::default().fg(Color::Cyan))
         .max(100)
         .render(f, area);
+    render_cursor(&h, area, f, view);
 }
 
 fn render_cpu_bars(app: &CPUTimeApp, area: Rect, f: &mut Frame<'_>, style: &Style) {
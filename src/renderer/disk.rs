@@ -3,15 +3,19 @@
  */
 
 use super::style::{max_style, ok_style};
-use super::{split_left_right_pane, FileSystemDisplay, Render};
+use super::{
+    history_sparkline, render_cursor, split_left_right_pane, top_at_offset, FileSystemDisplay,
+    Render,
+};
 use crate::float_to_byte_string;
 use crate::metrics::histogram::{HistogramKind, View};
+use crate::metrics::top::TopMetric;
 use crate::metrics::CPUTimeApp;
 use byte_unit::{Byte, Unit};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Sparkline};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
 use ratatui::Frame;
 use std::borrow::Cow;
 
@@ -81,6 +85,10 @@ pub fn render_disk(
         )
         .render(f, disk_layout[0]);
 }
+fn bytes_per_sec(v: f32) -> String {
+    format!("{}/s", float_to_byte_string!(v as f64, Unit::B))
+}
+
 fn disk_activity_histogram(
     app: &CPUTimeApp,
     f: &mut Frame<'_>,
@@ -101,18 +109,18 @@ fn disk_activity_histogram(
             None => return,
         };
 
-        let read_max: u64 = match h_read.data().iter().max() {
-            Some(x) => *x,
-            None => 1,
-        };
+        let read_max: u64 = h_read.recorded().max().unwrap_or(1);
         let read_max_bytes = float_to_byte_string!(read_max as f64, Unit::B);
 
-        let top_reader = match app.top_pids.read.pid {
-            Some(pid) => match app.process_map.get(&pid) {
-                Some(p) => format!("TOP [{:} - {:} - {:}]", p.pid, p.name, p.user_name),
+        let top_reader = match top_at_offset(app, &view, TopMetric::Read, bytes_per_sec) {
+            Some(top) => top,
+            None => match app.top_pids.read.pid {
+                Some(pid) => match app.process_map.get(&pid) {
+                    Some(p) => format!("TOP [{:} - {:} - {:}]", p.pid, p.name, p.user_name),
+                    None => String::from(""),
+                },
                 None => String::from(""),
             },
-            None => String::from(""),
         };
 
         let write_down =
@@ -125,18 +133,18 @@ fn disk_activity_histogram(
             None => return,
         };
 
-        let write_max: u64 = match h_write.data().iter().max() {
-            Some(x) => *x,
-            None => 1,
-        };
+        let write_max: u64 = h_write.recorded().max().unwrap_or(1);
         let write_max_bytes = float_to_byte_string!(write_max as f64, Unit::B);
 
-        let top_writer = match app.top_pids.write.pid {
-            Some(pid) => match app.process_map.get(&pid) {
-                Some(p) => format!("TOP [{:} - {:} - {:}]", p.pid, p.name, p.user_name),
+        let top_writer = match top_at_offset(app, &view, TopMetric::Write, bytes_per_sec) {
+            Some(top) => top,
+            None => match app.top_pids.write.pid {
+                Some(pid) => match app.process_map.get(&pid) {
+                    Some(p) => format!("TOP [{:} - {:} - {:}]", p.pid, p.name, p.user_name),
+                    None => String::from(""),
+                },
                 None => String::from(""),
             },
-            None => String::from(""),
         };
 
         #[cfg(target_os = "linux")]
@@ -150,44 +158,47 @@ fn disk_activity_histogram(
         ))];
 
         #[cfg(target_os = "linux")]
-        {
-            let mut top_io_waiter_style = ok_style();
-            let top_io_waiter = match app.top_pids.iowait.pid {
-                Some(pid) => match app.process_map.get(&pid) {
-                    Some(p) => {
-                        let iow = p.get_io_wait(&app.histogram_map.tick);
-                        if iow > 95.0 {
-                            top_io_waiter_style = max_style();
+        match top_at_offset(app, &view, TopMetric::IoWait, |v| format!("{v:.0}%")) {
+            Some(top) => spans.push(Span::raw(format!("IO WAIT {top}"))),
+            None => {
+                let mut top_io_waiter_style = ok_style();
+                let top_io_waiter = match app.top_pids.iowait.pid {
+                    Some(pid) => match app.process_map.get(&pid) {
+                        Some(p) => {
+                            let iow = p.get_io_wait(&app.histogram_map.tick);
+                            if iow > 95.0 {
+                                top_io_waiter_style = max_style();
+                            }
+                            format!("{:3.0}% {:} - {:} - {:}", iow, p.pid, p.name, p.user_name)
                         }
-                        format!("{:3.0}% {:} - {:} - {:}", iow, p.pid, p.name, p.user_name)
-                    }
+                        None => String::from(""),
+                    },
                     None => String::from(""),
-                },
-                None => String::from(""),
-            };
-            spans.push(Span::raw("IO WAIT [").to_owned());
-            spans.push(Span::styled(top_io_waiter, top_io_waiter_style));
-            spans.push(Span::raw("]"));
+                };
+                spans.push(Span::raw("IO WAIT [").to_owned());
+                spans.push(Span::styled(top_io_waiter, top_io_waiter_style));
+                spans.push(Span::raw("]"));
+            }
         }
 
-        Sparkline::default()
+        history_sparkline(&h_read)
             .block(Block::default().title(Line::from(spans)))
-            .data(h_read.data())
             .style(Style::default().fg(Color::LightYellow))
             .max(read_max)
             .render(f, area[0]);
+        render_cursor(&h_read, area[0], f, &view);
 
-        Sparkline::default()
+        history_sparkline(&h_write)
             .block(
                 Block::default().title(
                     format!("W [{write_down:^10}/s] PEAK [{write_max_bytes:^10}/s] {top_writer:}")
                         .as_str(),
                 ),
             )
-            .data(h_write.data())
             .style(Style::default().fg(Color::LightMagenta))
             .max(write_max)
             .render(f, area[1]);
+        render_cursor(&h_write, area[1], f, &view);
     }
 }
 
@@ -211,7 +222,7 @@ fn disk_usage(
         let free = float_to_byte_string!(fs.available_bytes as f64, Unit::B);
         let used = float_to_byte_string!(fs.get_used_bytes() as f64, Unit::B);
         let size = float_to_byte_string!(fs.size_bytes as f64, Unit::B);
-        Sparkline::default()
+        history_sparkline(&h_used)
             .block(
                 Block::default().title(
                     format!(
@@ -226,10 +237,10 @@ fn disk_usage(
                     .as_str(),
                 ),
             )
-            .data(h_used.data())
             .style(Style::default().fg(Color::LightYellow))
             .max(fs.size_bytes)
             .render(f, area[0]);
+        render_cursor(&h_used, area[0], f, &view);
         let columns = Layout::default()
             .margin(1)
             .direction(Direction::Horizontal)
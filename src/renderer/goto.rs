@@ -0,0 +1,48 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+use super::style::max_style;
+use super::Render;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+/// Prompt for the span of time the charts go to, over the top of `area`.
+pub fn render_goto(input: &str, error: Option<&str>, area: Rect, f: &mut Frame<'_>) {
+    let width = area.width.min(70);
+    let height = area.height.min(5);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.min(3),
+        width,
+        height,
+    }
+    .intersection(area);
+    let header_style = Style::default().fg(Color::Green);
+    let mut text = vec![
+        Line::from(vec![
+            Span::raw(" "),
+            Span::raw(input),
+            Span::styled("_", header_style),
+        ]),
+        Line::from(""),
+    ];
+    text.push(match error {
+        Some(e) => Line::from(Span::styled(format!(" {e}"), max_style())),
+        None => Line::from(Span::styled(
+            " e.g. -6h, 2026-10-16 03:00 or -2d to -1d  Go [RET] Cancel [ESC]",
+            header_style,
+        )),
+    });
+    Clear.render(f, popup);
+    Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(Span::styled(" Go to ", header_style))
+                .borders(Borders::ALL),
+        )
+        .render(f, popup);
+}
@@ -3,7 +3,7 @@
  */
 
 use super::style::{max_style, ok_style};
-use super::{percent_of, Render, LEFT_PANE_WIDTH};
+use super::{history_sparkline, percent_of, render_cursor, Render, LEFT_PANE_WIDTH};
 use crate::float_to_byte_string;
 use crate::metrics::histogram::{HistogramKind, View};
 use crate::metrics::CPUTimeApp;
@@ -11,7 +11,7 @@ use byte_unit::{Byte, Unit};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, List, ListItem, Sparkline};
+use ratatui::widgets::{Block, Borders, List, ListItem};
 use ratatui::Frame;
 use std::borrow::Cow;
 
@@ -90,12 +90,12 @@ pub fn render_graphics(
             version
         )
     };
-    Sparkline::default()
+    history_sparkline(&h_gpu)
         .block(Block::default().title(title.as_str()))
-        .data(h_gpu.data())
         .style(Style::default().fg(Color::LightYellow))
         .max(100)
         .render(f, area[0]);
+    render_cursor(&h_gpu, area[0], f, &view);
 
     let h_mem = match app
         .histogram_map
@@ -122,7 +122,7 @@ pub fn render_graphics(
             fan,
         )
     };
-    Sparkline::default()
+    history_sparkline(&h_mem)
         .block(Block::default().title(Line::from(vec![
             Span::raw(title2.as_str()),
             Span::raw(if gd.name == "Total" {
@@ -149,10 +149,10 @@ pub fn render_graphics(
             ),
             Span::raw("]"),
         ])))
-        .data(h_mem.data())
         .style(Style::default().fg(Color::LightMagenta))
         .max(100)
         .render(f, area[1]);
+    render_cursor(&h_mem, area[1], f, &view);
     let devices: Vec<_> = app
         .gfx_devices
         .iter()
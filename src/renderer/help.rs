@@ -11,12 +11,13 @@ use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
+use std::borrow::Cow;
 
 pub fn render_help(
     _app: &CPUTimeApp,
     area: Rect,
     f: &mut Frame<'_>,
-    history_recording: HistoryRecording,
+    history_recording: HistoryRecording<'_>,
 ) {
     let header_style = Style::default().fg(Color::Green);
     let main_style = Style::default();
@@ -33,11 +34,33 @@ pub fn render_help(
         ["+    ", "    Zoom chart in\n"],
         ["←    ", "    Move back in time\n"],
         ["→    ", "    Move forward In time\n"],
+        ["g    ", "    Go to a time, e.g. -6h or 2026-10-16 03:00\n"],
+        ["1-5  ", "    Show the last 5m, 1h, 6h, 24h or 7d\n"],
+        [
+            "l    ",
+            "    Show the listening ports, <RET> focuses the process\n",
+        ],
         ["`    ", "    Reset charts to current\n"],
     ];
 
+    static CHART_KEYS: &[[&str; 2]] = &[
+        [
+            "c     ",
+            "    Show/hide the cursor and the values under it\n",
+        ],
+        ["←/→   ", "    Move the cursor\n"],
+        [
+            "↑/↓   ",
+            "    Pick the file system, interface or graphics device\n",
+        ],
+        ["v     ", "    Mark the start of a range at the cursor\n"],
+        ["<RET> ", "    Mark the end and show the range statistics\n"],
+        ["<ESC> ", "    Cancel the range, then hide the cursor\n"],
+    ];
+
     static PROCESS_TABLE_KEYS: &[[&str; 2]] = &[
         ["<RET> ", "    Focus current process\n"],
+        ["↑/↓   ", "    Scroll the sockets of the focused process\n"],
         ["↓     ", "    Move one line down\n"],
         ["↑     ", "    Move one line up\n"],
         ["PgDown", "    Move view one screen down\n"],
@@ -52,6 +75,13 @@ pub fn render_help(
         ["<ESC> ", "    Leave filter mode\n"],
     ];
 
+    static REPLAY_KEYS: &[[&str; 2]] = &[
+        ["z     ", "    Pause/resume the recording\n"],
+        ["x     ", "    Step one tick while paused\n"],
+        ["]     ", "    Play faster\n"],
+        ["[     ", "    Play slower\n"],
+    ];
+
     let mut t = vec![Line::from(vec![Span::styled(
         "Primary Interface",
         header_style,
@@ -64,6 +94,16 @@ pub fn render_help(
         ]));
     }
 
+    t.push(Line::from(vec![Span::styled("", header_style)]));
+    t.push(Line::from(vec![Span::styled("Charts\n", header_style)]));
+
+    for [key, text] in CHART_KEYS {
+        t.push(Line::from(vec![
+            Span::styled(*key, key_style),
+            Span::styled(*text, main_style),
+        ]));
+    }
+
     t.push(Line::from(vec![Span::styled("", header_style)]));
     t.push(Line::from(vec![Span::styled(
         "Process Table\n",
@@ -77,21 +117,58 @@ pub fn render_help(
         ]));
     }
 
+    if let HistoryRecording::Replaying = history_recording {
+        t.push(Line::from(vec![Span::styled("", header_style)]));
+        t.push(Line::from(vec![Span::styled("Replay\n", header_style)]));
+        for [key, text] in REPLAY_KEYS {
+            t.push(Line::from(vec![
+                Span::styled(*key, key_style),
+                Span::styled(*text, main_style),
+            ]));
+        }
+    }
+
     let not_recording_reason = match history_recording {
         HistoryRecording::On => None,
         HistoryRecording::UserDisabled => {
-            Some("because zenith was started with the `--disable_history` flag\n")
+            Some("because zenith was started with the `--disable_history` flag\n".into())
+        }
+        HistoryRecording::OtherInstancePrevents(Some(e)) => Some(Cow::from(format!(
+            "because {}, start zenith with `--force-unlock` if that process is gone\n",
+            e
+        ))),
+        HistoryRecording::OtherInstancePrevents(None) => {
+            Some("because another zenith instance was already running\n".into())
+        }
+        HistoryRecording::StoreTooNew => {
+            Some("because the database was written by a newer version of zenith\n".into())
+        }
+        HistoryRecording::StoreUnreadable(e) => Some(Cow::from(format!(
+            "because {}, restart zenith once it can be read\n",
+            e
+        ))),
+        HistoryRecording::OtherHost => {
+            Some("because the database holds history recorded on another host\n".into())
         }
-        HistoryRecording::OtherInstancePrevents => {
-            Some("because another zenith instance was already running\n")
+        HistoryRecording::Replaying => {
+            Some("because zenith was started with `--replay` to play a recording\n".into())
         }
+        HistoryRecording::Viewing => Some(
+            "because zenith was started with `--view` to browse another instance's recording\n"
+                .into(),
+        ),
     };
 
     if let Some(reason) = not_recording_reason {
         t.push(Line::from(vec![Span::styled("", header_style)]));
-        for s in ["Recorded data is not being saved to the database\n", reason].iter() {
+        for s in [
+            "Recorded data is not being saved to the database\n",
+            reason.as_ref(),
+        ]
+        .iter()
+        {
             t.push(Line::from(vec![Span::styled(
-                *s,
+                s.to_string(),
                 Style::default().fg(Color::Yellow),
             )]));
         }
@@ -4,16 +4,23 @@
 
 mod cpu;
 mod disk;
+mod goto;
 mod graphics;
 mod help;
 pub mod macros;
 mod network;
+mod ports;
 mod process;
+mod range;
 pub mod section;
 pub mod style;
 mod title;
 use crate::metrics::graphics::device::GraphicsExt;
-use crate::metrics::histogram::View;
+use crate::metrics::histogram::{Cursor, Histogram, Retention, View};
+use crate::metrics::network::NetTotal;
+use crate::metrics::session::{Player, Recorder, Session};
+use crate::metrics::snapshots::{ProcessSnapshots, ProcessView};
+use crate::metrics::top::{consumers_of, TopMetric};
 use crate::metrics::zprocess::*;
 use crate::metrics::*;
 use crate::renderer::section::{sum_section_heights, Section, SectionMGRList};
@@ -24,21 +31,33 @@ use crossterm::{
     terminal::EnterAlternateScreen,
 };
 use num_traits::FromPrimitive;
+use process::ProcessesShown;
+use range::RangeStats;
 use ratatui::{backend::CrosstermBackend, Terminal};
+use std::borrow::Cow;
 use std::cmp::Eq;
 use std::io;
 use std::io::Stdout;
 use std::path::PathBuf;
 use std::rc::Rc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
-use ratatui::widgets::{Block, Borders};
+use ratatui::symbols;
+use ratatui::widgets::{Block, Borders, Sparkline, TableState};
 use ratatui::Frame;
 
 const PROCESS_SELECTION_GRACE: Duration = Duration::from_millis(2000);
 const LEFT_PANE_WIDTH: u16 = 34u16;
+/// Spans of time the charts are set to with the number keys.
+const PRESET_SPANS: [Duration; 5] = [
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(6 * 60 * 60),
+    Duration::from_secs(24 * 60 * 60),
+    Duration::from_secs(7 * 24 * 60 * 60),
+];
 
 /// Compatibility trait, that preserves an older method from tui 0.6.5
 /// Exists mostly to keep the caller code idiomatic for the use cases in this file
@@ -100,11 +119,97 @@ fn split_left_right_pane(
     (layout, view)
 }
 
+/// Sparkline of a history that shades the ticks without data instead of drawing them as zero.
+fn history_sparkline<'a>(h: &Histogram) -> Sparkline<'a> {
+    Sparkline::default()
+        .data(h.points())
+        .absent_value_symbol(symbols::shade::LIGHT)
+        .absent_value_style(style::no_data_style())
+}
+
+/// Highlights the cursor and the range being selected on the chart of `h` drawn in `area`.
+fn render_cursor(h: &Histogram, area: Rect, f: &mut Frame<'_>, view: &View) {
+    let Some(cursor) = view.cursor else {
+        return;
+    };
+    // below the title
+    let chart = Rect {
+        y: area.y + 1,
+        height: area.height.saturating_sub(1),
+        ..area
+    };
+    let shown = h.points().count().min(usize::from(chart.width));
+    if shown == 0 {
+        return;
+    }
+    let x = |column: usize| chart.x + (shown - 1 - column) as u16;
+    if let Some(mark) = cursor.mark {
+        let newest = mark.min(cursor.column);
+        let oldest = mark.max(cursor.column).min(shown - 1);
+        if newest <= oldest {
+            let range = Rect {
+                x: x(oldest),
+                width: (oldest - newest + 1) as u16,
+                ..chart
+            };
+            f.buffer_mut().set_style(range, style::range_style());
+        }
+    }
+    if cursor.column < shown {
+        let column = Rect {
+            x: x(cursor.column),
+            width: 1,
+            ..chart
+        };
+        f.buffer_mut().set_style(column, style::cursor_style());
+    }
+}
+
+/// The processes that used the most of `metric` in the newest column of a chart scrolled back
+/// in time, e.g. `TOP AT 13:45:10 [1234 - rustc - 98%]`. None while the chart shows the present.
+fn top_at_offset(
+    app: &CPUTimeApp,
+    view: &View,
+    metric: TopMetric,
+    value: impl Fn(f32) -> String,
+) -> Option<String> {
+    if view.offset == 0 {
+        return None;
+    }
+    let hm = &app.histogram_map;
+    let zoom = view.zoom_factor.max(1) as usize;
+    // the newest column shown, as the cursor counts it
+    let age = hm.column_age(view, 0);
+    let consumers = hm.top_at(age, zoom);
+    let top: Vec<String> = consumers_of(&consumers, metric)
+        .map(|c| format!("{} - {} - {}", c.pid, c.name, value(c.value)))
+        .collect();
+    let at = hm
+        .time_at(age)
+        .map(|t| {
+            chrono::DateTime::<chrono::Local>::from(t)
+                .format(" AT %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let top = if top.is_empty() {
+        String::from("none recorded")
+    } else {
+        top.join(", ")
+    };
+    Some(format!("TOP{} [{}]", at, top))
+}
+
 /// current size of the terminal returned as (columns, rows)
 fn terminal_size() -> (u16, u16) {
     crossterm::terminal::size().expect("Failed to get terminal size")
 }
 
+/// Columns of the charts on a terminal `width` columns wide, the same for every section.
+fn chart_width(width: u16) -> usize {
+    usize::from(width.saturating_sub(LEFT_PANE_WIDTH + 2))
+}
+
 /// ceil to nearest upper even number
 macro_rules! ceil_even {
     ($x:expr) => {
@@ -185,9 +290,15 @@ pub struct TerminalRenderer<'a> {
     events: Events,
     process_table_row_start: usize,
     process_table_height: u16,
+    /// First row of the socket list of the selected process and how many rows are shown.
+    socket_row_start: usize,
+    socket_list_height: u16,
     gfx_device_index: usize,
     file_system_index: usize,
     file_system_display: FileSystemDisplay,
+    /// Interface shown in the network section, the total if None. Kept by name as interfaces
+    /// come and go.
+    net_interface: Option<String>,
     /// Index in the vector below is "order" on the screen starting from the top
     /// (usually CPU) while value is the section it belongs to and its current height (as %).
     /// Currently all sections are stacked on top of one another horizontally and
@@ -216,18 +327,45 @@ pub struct TerminalRenderer<'a> {
     selection_grace_start: Option<Instant>,
     section_manager_options: SectionMGRList<'a>,
     disable_history: bool,
+    /// Why the database couldn't be locked for recording.
+    lock_error: Option<String>,
+    process_snapshots: ProcessSnapshots,
+    /// The process table at the newest column of the charts while they show the past.
+    past_processes: Option<ProcessView>,
+    recorder: Option<Recorder>,
+    /// Why writing the recording stopped.
+    record_error: Option<String>,
+    player: Option<Player>,
+    /// Column of the charts under the cursor, counted from the newest one shown.
+    cursor: Option<usize>,
+    /// Time of the newest sample in the column where the range being selected starts.
+    range_start: Option<SystemTime>,
+    range_stats: Option<RangeStats>,
+    /// Input of the go to prompt while it is open.
+    goto: Option<String>,
+    goto_error: Option<String>,
+    /// The row picked in the listening ports panel while it is open.
+    ports: Option<TableState>,
     recompute_constraints_on_start_up: bool,
 }
 
 impl TerminalRenderer<'_> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         tick_rate: u64,
         section_geometry: &'_ [(Section, f64)],
         db_path: Option<PathBuf>,
         disable_history: bool,
+        lock_error: Option<String>,
+        view: bool,
+        retention: Retention,
+        process_snapshots: usize,
+        net_total: NetTotal,
+        session: Session,
     ) -> TerminalRenderer<'_> {
         debug!("Create Metrics App");
-        let mut app = CPUTimeApp::new(Duration::from_millis(tick_rate), db_path);
+        let mut app = CPUTimeApp::new(Duration::from_millis(tick_rate), db_path, view, retention);
+        app.net_total = net_total;
         debug!("Create Event Loop");
         let events = Events::new(app.histogram_map.tick);
 
@@ -238,7 +376,21 @@ impl TerminalRenderer<'_> {
         let constraints = get_constraints(section_geometry, terminal_size().1);
         let mut section_geometry = section_geometry.to_vec();
         let mut recompute_constraints_on_start_up = false;
-        app.update_gfx_devices();
+        let (recorder, mut player) = match session {
+            Session::Live => (None, None),
+            Session::Record(recorder) => (Some(recorder), None),
+            Session::Replay(player) => (None, Some(player)),
+        };
+        match player.as_mut() {
+            Some(player) => {
+                // the panels and devices of the recorded machine
+                app.replay_host(player.host());
+                if let Some(tick) = player.next_tick() {
+                    app.replay(tick, false);
+                }
+            }
+            None => app.update_gfx_devices(),
+        }
         if app.gfx_devices.is_empty()
             && section_geometry
                 .iter()
@@ -252,9 +404,12 @@ impl TerminalRenderer<'_> {
             events,
             process_table_row_start: 0,
             process_table_height: 0,
+            socket_row_start: 0,
+            socket_list_height: 0,
             gfx_device_index: 0,
             file_system_index: 0,
             file_system_display: FileSystemDisplay::Activity,
+            net_interface: None,
             section_geometry: section_geometry.clone(),
             zoom_factor: 1,
             update_number: 0,
@@ -273,6 +428,18 @@ impl TerminalRenderer<'_> {
             selection_grace_start: None,
             section_manager_options: SectionMGRList::with_geometry(section_geometry),
             disable_history,
+            lock_error,
+            process_snapshots: ProcessSnapshots::new(process_snapshots),
+            past_processes: None,
+            recorder,
+            record_error: None,
+            player,
+            cursor: None,
+            range_start: None,
+            range_stats: None,
+            goto: None,
+            goto_error: None,
+            ports: None,
             recompute_constraints_on_start_up,
         }
     }
@@ -334,11 +501,23 @@ impl TerminalRenderer<'_> {
             f,
             &self.zoom_factor,
             &self.hist_start_offset,
+            self.session_status().as_deref(),
         );
-        let history_recording = match (self.app.writes_db_store(), self.disable_history) {
-            (true, _) => HistoryRecording::On,
-            (false, true) => HistoryRecording::UserDisabled,
-            (false, false) => HistoryRecording::OtherInstancePrevents,
+        let history_recording = match (
+            self.app.writes_db_store(),
+            self.disable_history,
+            self.app.newer_store(),
+        ) {
+            (true, _, _) => HistoryRecording::On,
+            _ if self.player.is_some() => HistoryRecording::Replaying,
+            _ if self.app.histogram_map.viewing().is_some() => HistoryRecording::Viewing,
+            (false, true, _) => HistoryRecording::UserDisabled,
+            (false, false, Some(_)) => HistoryRecording::StoreTooNew,
+            _ if self.app.other_host().is_some() => HistoryRecording::OtherHost,
+            (false, false, None) => match self.app.unreadable_store() {
+                Some(e) => HistoryRecording::StoreUnreadable(e),
+                None => HistoryRecording::OtherInstancePrevents(self.lock_error.as_deref()),
+            },
         };
         help::render_help(&self.app, v_sections[1], f, history_recording);
     }
@@ -355,6 +534,7 @@ impl TerminalRenderer<'_> {
             f,
             &self.zoom_factor,
             &self.hist_start_offset,
+            self.session_status().as_deref(),
         );
         section::render_section_mgr(&mut self.section_manager_options, v_sections[1], f);
     }
@@ -379,13 +559,9 @@ impl TerminalRenderer<'_> {
                 f,
                 &self.zoom_factor,
                 &self.hist_start_offset,
+                self.session_status().as_deref(),
             );
-            let view = View {
-                zoom_factor: self.zoom_factor,
-                update_number: self.update_number,
-                width: 0,
-                offset: self.hist_start_offset,
-            };
+            let view = self.view();
             let geometry = &self.section_geometry.to_vec();
 
             for section_index in 0..geometry.len() {
@@ -399,9 +575,14 @@ impl TerminalRenderer<'_> {
                     };
                 match current_section {
                     Section::Cpu => cpu::render_cpu(&self.app, v_section, f, view, border_style),
-                    Section::Network => {
-                        network::render_net(&self.app, v_section, f, view, border_style)
-                    }
+                    Section::Network => network::render_net(
+                        &self.app,
+                        v_section,
+                        f,
+                        view,
+                        border_style,
+                        self.net_interface.as_deref(),
+                    ),
                     Section::Disk => disk::render_disk(
                         &self.app,
                         v_section,
@@ -421,18 +602,20 @@ impl TerminalRenderer<'_> {
                     ),
                     Section::Process => {
                         if let Some(p) = self.app.selected_process.as_ref() {
-                            process::render_process(
+                            self.socket_list_height = process::render_process(
                                 &self.app,
                                 v_section,
                                 f,
                                 border_style,
                                 &self.process_message,
                                 p,
+                                self.socket_row_start,
                             );
                         } else {
                             self.highlighted_process = process::render_process_table(
                                 &self.app,
-                                &process::filter_process_table(&self.app, &self.filter),
+                                &self.processes_shown(),
+                                &self.process_table(),
                                 v_section,
                                 self.process_table_row_start,
                                 f,
@@ -449,22 +632,212 @@ impl TerminalRenderer<'_> {
                         }
                     }
                 }
+                if let (Some(cursor), true) = (view.cursor, current_section != Section::Process) {
+                    let series = range::section_series(
+                        &self.app,
+                        current_section,
+                        self.file_system_index,
+                        self.gfx_device_index,
+                        self.net_interface.as_deref(),
+                    );
+                    range::render_cursor_values(
+                        &self.app,
+                        &series,
+                        &view,
+                        cursor.column,
+                        v_section,
+                        f,
+                    );
+                }
+            }
+            if let Some(stats) = &self.range_stats {
+                range::render_range_stats(stats, f.area(), f);
+            }
+            if let Some(input) = &self.goto {
+                goto::render_goto(input, self.goto_error.as_deref(), f.area(), f);
+            }
+            if let Some(state) = self.ports.as_mut() {
+                ports::render_ports(&self.app, state, self.player.is_some(), f.area(), f);
             }
         }
     }
 
+    /// The charts as scrolled and zoomed, their width is set by each section.
+    fn view(&self) -> View {
+        let mut view = View {
+            zoom_factor: self.zoom_factor,
+            update_number: self.update_number,
+            width: 0,
+            offset: self.hist_start_offset,
+            cursor: None,
+        };
+        let hm = &self.app.histogram_map;
+        view.cursor = self.cursor.map(|column| Cursor {
+            column,
+            mark: self
+                .range_start
+                .and_then(|t| hm.age_at(t))
+                .map(|age| hm.age_column(&view, age)),
+        });
+        view
+    }
+
+    /// Starts selecting a range of the charts at the cursor.
+    fn start_range(&mut self) {
+        if self.selected_section() == Section::Process {
+            return;
+        }
+        let view = self.view();
+        let column = self.cursor.unwrap_or(0);
+        let hm = &self.app.histogram_map;
+        self.range_start = hm.time_at(hm.column_age(&view, column));
+        if self.range_start.is_some() {
+            self.cursor = Some(column);
+        }
+    }
+
+    /// Ends the range at the cursor and shows the statistics of the selected section over it.
+    fn end_range(&mut self) {
+        let view = self.view();
+        self.range_start = None;
+        let Some(cursor) = view.cursor else {
+            return;
+        };
+        let mark = cursor.mark.unwrap_or(cursor.column);
+        let section = self.selected_section();
+        let series = range::section_series(
+            &self.app,
+            section,
+            self.file_system_index,
+            self.gfx_device_index,
+            self.net_interface.as_deref(),
+        );
+        self.range_stats = Some(RangeStats::new(
+            &self.app,
+            section,
+            &series,
+            &view,
+            cursor.column.min(mark),
+            cursor.column.max(mark),
+        ));
+    }
+
+    /// Drops the range being selected, or hides the cursor if there is none.
+    fn cancel_range(&mut self) {
+        if self.range_start.take().is_none() {
+            self.cursor = None;
+        }
+    }
+
+    /// Shows the cursor on the newest column of the charts, or hides it.
+    fn toggle_cursor(&mut self) {
+        if self.cursor.is_some() {
+            self.cursor = None;
+            self.range_start = None;
+        } else if self.selected_section() != Section::Process {
+            self.cursor = Some(0);
+        }
+    }
+
+    /// Time of the newest sample, what relative times in the go to prompt count from.
+    fn newest_time(&self) -> SystemTime {
+        self.app
+            .histogram_map
+            .time_at(0)
+            .unwrap_or_else(SystemTime::now)
+    }
+
+    /// Zooms and scrolls the charts so `from` to `to` fills them.
+    fn show_window(&mut self, from: SystemTime, to: SystemTime) -> Result<(), String> {
+        let (zoom_factor, offset) = self
+            .app
+            .histogram_map
+            .window_view(from, to, chart_width(terminal_size().0))
+            .ok_or("Nothing has been recorded yet")?;
+        self.zoom_factor = zoom_factor;
+        self.hist_start_offset = offset;
+        self.update_number = 0;
+        Ok(())
+    }
+
+    /// Shows the last `span` of the history, or opens the go to prompt with why it can't.
+    fn show_span(&mut self, span: Duration) {
+        let newest = self.newest_time();
+        let from = newest.checked_sub(span).unwrap_or(SystemTime::UNIX_EPOCH);
+        if let Err(e) = self.show_window(from, newest) {
+            self.goto = Some(String::new());
+            self.goto_error = Some(e);
+        }
+    }
+
+    fn process_goto_input(&mut self, input: KeyEvent) {
+        let now = self.newest_time();
+        let Some(text) = self.goto.as_mut() else {
+            return;
+        };
+        match input.code {
+            Key::Esc => {
+                self.goto = None;
+                self.goto_error = None;
+            }
+            Key::Enter => {
+                let window = parse_window(text, now);
+                match window.and_then(|(from, to)| self.show_window(from, to)) {
+                    Ok(()) => {
+                        self.goto = None;
+                        self.goto_error = None;
+                    }
+                    Err(e) => self.goto_error = Some(e),
+                }
+            }
+            Key::Char(c) => {
+                text.push(c);
+                self.goto_error = None;
+            }
+            Key::Backspace | Key::Delete => {
+                text.pop();
+                self.goto_error = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the cursor back in time, scrolling the charts at their oldest column.
+    fn cursor_left(&mut self, column: usize) {
+        if column + 1 < chart_width(terminal_size().0) {
+            self.cursor = Some(column + 1);
+        } else {
+            self.histogram_left();
+        }
+    }
+
+    /// Moves the cursor forward in time, scrolling the charts at their newest column.
+    fn cursor_right(&mut self, column: usize) {
+        if column > 0 {
+            self.cursor = Some(column - 1);
+        } else {
+            self.histogram_right();
+        }
+    }
+
     pub async fn start(&mut self, mut terminal: Terminal<CrosstermBackend<Stdout>>) {
         debug!("Starting Main Loop.");
         if self.recompute_constraints_on_start_up {
             self.recompute_constraints();
             self.recompute_constraints_on_start_up = false;
         }
+        if self.app.histogram_map.viewing().is_some() {
+            // system details and devices for the panels, the charts come from the database
+            self.app.update(false).await;
+            self.app.reload_history();
+        }
         loop {
+            self.update_past_processes();
             terminal
                 .draw(|f| self.render_frame(f))
                 .expect("Could not draw frame.");
 
-            let process_table = process::filter_process_table(&self.app, &self.filter);
+            let process_table = self.process_table().into_owned();
 
             if !process_table.is_empty() && self.highlighted_row >= process_table.len() {
                 self.highlighted_row = process_table.len() - 1;
@@ -472,7 +845,6 @@ impl TerminalRenderer<'_> {
             let event = self.events.next().expect("No new event.");
             let action = match event {
                 Event::Input(input) => {
-                    let process_table = process_table.into_owned();
                     self.process_key_event(input, &process_table, self.process_table_height)
                         .await
                 }
@@ -512,16 +884,123 @@ impl TerminalRenderer<'_> {
             }
         }
 
-        let keep_order =
-            self.app.selected_process.is_some() || self.selection_grace_start.is_some();
-
-        self.app.update(keep_order).await;
+        if self.app.histogram_map.viewing().is_some() {
+            self.app.reload_history();
+        } else if let Some(player) = self.player.as_mut() {
+            let due = player.due();
+            self.replay_ticks(due);
+        } else {
+            self.app.update(self.keep_order()).await;
+            if self.ports.is_some() {
+                self.app.update_listening_ports();
+            }
+            self.record_process_snapshot();
+            if let Some(recorder) = self.recorder.as_mut() {
+                if let Err(e) = recorder.record(&self.app) {
+                    let e = format!("Recording to {} stopped: {}", recorder.path().display(), e);
+                    error!("{}", e);
+                    self.record_error = Some(e);
+                    self.recorder = None;
+                }
+            }
+        }
         self.update_number += 1;
         if self.update_number == self.zoom_factor {
             self.update_number = 0;
         }
     }
 
+    /// Keeps the order of the process table while a process is or was just selected.
+    fn keep_order(&self) -> bool {
+        self.app.selected_process.is_some() || self.selection_grace_start.is_some()
+    }
+
+    fn record_process_snapshot(&mut self) {
+        if let Some(time) = self.app.histogram_map.time_at(0) {
+            self.process_snapshots.record(
+                time,
+                self.app.process_map.values(),
+                &self.app.histogram_map.tick,
+            );
+        }
+    }
+
+    /// Shows the next `n` ticks of the recording that is replayed.
+    fn replay_ticks(&mut self, n: usize) {
+        let keep_order = self.keep_order();
+        for _ in 0..n {
+            let Some(tick) = self.player.as_mut().and_then(|p| p.next_tick()) else {
+                break;
+            };
+            self.app.replay(tick, keep_order);
+            self.record_process_snapshot();
+        }
+    }
+
+    /// Shown in the title bar instead of the history warning.
+    fn session_status(&self) -> Option<String> {
+        match &self.player {
+            Some(player) => {
+                let state = if player.ended() {
+                    " ENDED"
+                } else if player.paused() {
+                    " PAUSED"
+                } else {
+                    ""
+                };
+                Some(format!(
+                    " Replaying {} {}x{} ",
+                    player.path().display(),
+                    player.speed(),
+                    state
+                ))
+            }
+            None => self.record_error.as_ref().map(|e| format!(" {} ", e)),
+        }
+    }
+
+    /// Picks the snapshot of the process table for the newest column of the charts while they
+    /// are scrolled back.
+    fn update_past_processes(&mut self) {
+        // the newest column shown, as the cursor and the range statistics count it
+        let age = self.app.histogram_map.column_age(&self.view(), 0);
+        self.past_processes = match self.app.histogram_map.time_at(age) {
+            Some(time) if self.hist_start_offset > 0 && self.process_snapshots.enabled() => {
+                self.process_snapshots.at(
+                    time,
+                    &self.app.histogram_map.tick,
+                    self.app.psortby,
+                    self.app.psortorder,
+                )
+            }
+            _ => None,
+        };
+    }
+
+    fn processes_shown(&self) -> ProcessesShown<'_> {
+        match &self.past_processes {
+            Some(past) => ProcessesShown::Past(past),
+            None if self.hist_start_offset > 0 && self.process_snapshots.enabled() => {
+                ProcessesShown::NoSnapshot
+            }
+            None => ProcessesShown::Live,
+        }
+    }
+
+    /// Pids of the rows of the process table, in order.
+    fn process_table(&self) -> Cow<'_, [u32]> {
+        match &self.past_processes {
+            Some(past) => {
+                process::filter_process_table(&past.process_map, &past.processes, &self.filter)
+            }
+            None => process::filter_process_table(
+                &self.app.process_map,
+                &self.app.processes,
+                &self.filter,
+            ),
+        }
+    }
+
     async fn process_key_event(
         &mut self,
         input: KeyEvent,
@@ -529,6 +1008,27 @@ impl TerminalRenderer<'_> {
         process_table_height: u16,
     ) -> Action {
         debug!("Event Key: {:?}", input);
+        if self.range_stats.is_some() {
+            self.range_stats = None;
+            if input.code == Key::Char('c') && input.modifiers.contains(KeyModifiers::CONTROL) {
+                return Action::Quit;
+            }
+            return Action::Continue;
+        }
+        if self.goto.is_some() {
+            if input.code == Key::Char('c') && input.modifiers.contains(KeyModifiers::CONTROL) {
+                return Action::Quit;
+            }
+            self.process_goto_input(input);
+            return Action::Continue;
+        }
+        if self.ports.is_some() {
+            if input.code == Key::Char('c') && input.modifiers.contains(KeyModifiers::CONTROL) {
+                return Action::Quit;
+            }
+            self.process_ports_input(input);
+            return Action::Continue;
+        }
         match input.code {
             Key::Up => self.view_up(process_table, 1),
             Key::PageUp => self.view_up(process_table, process_table_height.into()),
@@ -544,14 +1044,23 @@ impl TerminalRenderer<'_> {
                 process_table_height.into(),
                 process_table.len(),
             ),
-            Key::Left => self.histogram_left(),
-            Key::Right => self.histogram_right(),
+            Key::Left => match self.cursor {
+                Some(column) => self.cursor_left(column),
+                None => self.histogram_left(),
+            },
+            Key::Right => match self.cursor {
+                Some(column) => self.cursor_right(column),
+                None => self.histogram_right(),
+            },
+            Key::Enter if self.range_start.is_some() => self.end_range(),
             Key::Enter => self.select(),
             Key::Char('c') => {
                 if input.modifiers.contains(KeyModifiers::CONTROL) {
                     return Action::Quit;
                 } else if self.show_find {
                     self.process_find_input(input);
+                } else {
+                    self.toggle_cursor();
                 }
             }
             _other => {
@@ -567,14 +1076,94 @@ impl TerminalRenderer<'_> {
 
     fn select(&mut self) {
         let selected = self.selected_section();
-        if selected == Section::Process {
-            self.app.select_process(self.highlighted_process.take());
-            self.process_message = None;
-            self.show_find = false;
-            self.process_table_row_start = 0;
+        // the pid of a process in a snapshot may belong to another process by now
+        if selected == Section::Process && self.past_processes.is_none() {
+            let highlighted = self.highlighted_process.take();
+            self.focus_process(highlighted);
         }
     }
 
+    /// Shows the details of `process` in the process section.
+    fn focus_process(&mut self, process: Option<Box<ZProcess>>) {
+        self.app.select_process(process);
+        if self.player.is_some() {
+            self.app.process_sockets = Err(String::from("Sockets aren't recorded."));
+        } else {
+            self.app.update_process_sockets();
+        }
+        self.socket_row_start = 0;
+        self.process_message = None;
+        self.show_find = false;
+        self.process_table_row_start = 0;
+    }
+
+    fn open_ports(&mut self) {
+        if self.player.is_none() {
+            self.app.update_listening_ports();
+        }
+        self.ports = Some(TableState::default().with_selected(0));
+    }
+
+    fn process_ports_input(&mut self, input: KeyEvent) {
+        use std::cmp::min;
+        let Some(state) = self.ports.as_mut() else {
+            return;
+        };
+        let last = self.app.listening_ports.len().saturating_sub(1);
+        let selected = state.selected().unwrap_or(0).min(last);
+        match input.code {
+            Key::Esc | Key::Char('l') => self.ports = None,
+            Key::Up => state.select(Some(selected.saturating_sub(1))),
+            Key::Down => state.select(Some(min(selected + 1, last))),
+            Key::PageUp => state.select(Some(selected.saturating_sub(10))),
+            Key::PageDown => state.select(Some(min(selected + 10, last))),
+            Key::Home => state.select(Some(0)),
+            Key::End => state.select(Some(last)),
+            Key::Enter => self.show_port_owner(selected),
+            _ => {}
+        }
+    }
+
+    /// Closes the ports panel and shows the details of the process holding the `index`th port,
+    /// the first of them if several do.
+    fn show_port_owner(&mut self, index: usize) {
+        let process = self
+            .app
+            .listening_ports
+            .get(index)
+            .and_then(|port| port.pids.first())
+            .and_then(|pid| self.app.process_map.get(pid));
+        let Some(process) = process.cloned() else {
+            return;
+        };
+        self.ports = None;
+        if let Some(i) = self
+            .section_geometry
+            .iter()
+            .position(|(s, _)| *s == Section::Process)
+        {
+            self.selected_section_index = i;
+        }
+        self.focus_process(Some(Box::new(process)));
+    }
+
+    /// Moves to the next or previous interface of the network section, the total comes first.
+    fn select_net_interface(&mut self, next: bool) {
+        let names: Vec<Option<&String>> = std::iter::once(None)
+            .chain(self.app.interfaces.iter().map(|i| Some(&i.name)))
+            .collect();
+        let i = names
+            .iter()
+            .position(|n| n.map(String::as_str) == self.net_interface.as_deref())
+            .unwrap_or(0);
+        let i = if next {
+            (i + 1).min(names.len() - 1)
+        } else {
+            i.saturating_sub(1)
+        };
+        self.net_interface = names[i].cloned();
+    }
+
     fn view_up(&mut self, process_table: &[u32], delta: usize) {
         let selected = self.selected_section();
         if self.show_section_mgr {
@@ -598,8 +1187,14 @@ impl TerminalRenderer<'_> {
             if self.file_system_index > 0 {
                 self.file_system_index -= 1;
             }
+        } else if selected == Section::Network {
+            self.select_net_interface(false);
         } else if selected == Section::Process {
-            if self.app.selected_process.is_some() || process_table.is_empty() {
+            if self.app.selected_process.is_some() {
+                self.socket_row_start = self.socket_row_start.saturating_sub(delta);
+                return;
+            }
+            if process_table.is_empty() {
                 return;
             }
 
@@ -639,8 +1234,18 @@ impl TerminalRenderer<'_> {
             if self.file_system_index < self.app.disks.len() - 1 {
                 self.file_system_index += 1;
             }
+        } else if selected == Section::Network {
+            self.select_net_interface(true);
         } else if selected == Section::Process {
-            if self.app.selected_process.is_some() || process_table.is_empty() {
+            if self.app.selected_process.is_some() {
+                let sockets = self.app.process_sockets.as_ref().map_or(0, |s| s.len());
+                self.socket_row_start = min(
+                    self.socket_row_start + delta,
+                    sockets.saturating_sub(self.socket_list_height.into()),
+                );
+                return;
+            }
+            if process_table.is_empty() {
                 return;
             }
 
@@ -675,6 +1280,27 @@ impl TerminalRenderer<'_> {
         }
     }
 
+    /// Zoom steps grow with the zoom factor so the rollup tiers are reachable.
+    fn zoom_step(zoom_factor: u32) -> u32 {
+        (zoom_factor / 10).max(1)
+    }
+
+    fn zoom_in(&mut self) {
+        if self.zoom_factor > 1 {
+            self.zoom_factor -= Self::zoom_step(self.zoom_factor - 1);
+        }
+        self.update_number = 0;
+    }
+
+    fn zoom_out(&mut self) {
+        let width = chart_width(terminal_size().0);
+        let max_zoom = self.app.histogram_map.max_zoom_factor(width);
+        if self.zoom_factor < max_zoom {
+            self.zoom_factor = (self.zoom_factor + Self::zoom_step(self.zoom_factor)).min(max_zoom);
+        }
+        self.update_number = 0;
+    }
+
     fn process_find_input(&mut self, input: KeyEvent) {
         match input.code {
             Key::Esc => {
@@ -737,6 +1363,14 @@ impl TerminalRenderer<'_> {
     }
 
     async fn process_toplevel_input(&mut self, input: KeyEvent) -> Action {
+        if self.player.is_some() && self.app.selected_process.is_some() {
+            if let Key::Char('s' | 'r' | 'k' | 't' | 'n' | 'p') = input.code {
+                // the pid belongs to the recorded machine
+                self.process_message =
+                    Some(String::from("Processes of a recording can't be changed."));
+                return Action::Continue;
+            }
+        }
         match input.code {
             Key::Char('q') => {
                 return Action::Quit;
@@ -771,16 +1405,13 @@ impl TerminalRenderer<'_> {
                 self.app.sort_process_table();
             }
             Key::Char('+') | Key::Char('=') => {
-                if self.zoom_factor > 1 {
-                    self.zoom_factor -= 1;
-                }
-                self.update_number = 0;
+                self.zoom_in();
             }
             Key::Char('-') => {
-                if self.zoom_factor < 100 {
-                    self.zoom_factor += 1;
-                }
-                self.update_number = 0;
+                self.zoom_out();
+            }
+            Key::Esc if self.cursor.is_some() => {
+                self.cancel_range();
             }
             Key::Esc | Key::Char('b') => {
                 self.app.selected_process = None;
@@ -859,6 +1490,37 @@ impl TerminalRenderer<'_> {
                 self.highlighted_row = 0;
                 self.process_table_row_start = 0;
             }
+            Key::Char('z') if self.player.is_some() => {
+                if let Some(player) = self.player.as_mut() {
+                    player.toggle_pause();
+                }
+            }
+            Key::Char('x') if self.player.as_ref().is_some_and(|p| p.paused()) => {
+                self.replay_ticks(1);
+                self.update_number += 1;
+            }
+            Key::Char(']') => {
+                if let Some(player) = self.player.as_mut() {
+                    player.faster();
+                }
+            }
+            Key::Char('[') => {
+                if let Some(player) = self.player.as_mut() {
+                    player.slower();
+                }
+            }
+            Key::Char('v') => {
+                self.start_range();
+            }
+            Key::Char('g') => {
+                self.goto = Some(String::new());
+            }
+            Key::Char('l') => {
+                self.open_ports();
+            }
+            Key::Char(c @ '1'..='5') => {
+                self.show_span(PRESET_SPANS[c as usize - '1' as usize]);
+            }
             Key::Char('a') => {
                 if self.file_system_display == FileSystemDisplay::Activity {
                     self.file_system_display = FileSystemDisplay::Usage;
@@ -879,8 +1541,15 @@ enum Action {
     Quit,
 }
 
-pub enum HistoryRecording {
+pub enum HistoryRecording<'a> {
     On,
     UserDisabled,
-    OtherInstancePrevents,
+    /// Another process holds the lock, described if known.
+    OtherInstancePrevents(Option<&'a str>),
+    StoreTooNew,
+    /// Reading the store failed, as described.
+    StoreUnreadable(&'a str),
+    OtherHost,
+    Viewing,
+    Replaying,
 }
@@ -2,24 +2,32 @@
  * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
  */
 
-use super::{split_left_right_pane, Render};
+use super::style::{max_style, ok_style};
+#[cfg(target_os = "linux")]
+use super::top_at_offset;
+use super::{history_sparkline, render_cursor, split_left_right_pane, Render};
 use crate::float_to_byte_string;
 use crate::metrics::histogram::{HistogramKind, View};
+use crate::metrics::network::InterfaceTraffic;
+#[cfg(target_os = "linux")]
+use crate::metrics::top::TopMetric;
 use crate::metrics::CPUTimeApp;
 use byte_unit::{Byte, Unit};
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
-use ratatui::text::Span;
-use ratatui::widgets::{Block, Borders, List, ListItem, Sparkline};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem};
 use ratatui::Frame;
 use std::borrow::Cow;
 
+/// Traffic of the interface `net_interface`, or of all counted in the total if None.
 pub fn render_net(
     app: &CPUTimeApp,
     area: Rect,
     f: &mut Frame<'_>,
     view: View,
     border_style: Style,
+    net_interface: Option<&str>,
 ) {
     let (network_layout, view) = split_left_right_pane("Network", area, f, view, border_style);
     let net = Layout::default()
@@ -28,72 +36,186 @@ pub fn render_net(
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(network_layout[1]);
 
-    let net_up = float_to_byte_string!(
-        app.net_out as f64 / app.histogram_map.tick.as_secs_f64(),
-        Unit::B
-    );
-    let h_out = match app.histogram_map.get_zoomed(&HistogramKind::NetTx, &view) {
-        Some(h) => h,
-        None => return,
-    };
-
-    let up_max: u64 = match h_out.data().iter().max() {
-        Some(x) => *x,
-        None => 1,
+    let (tx_kind, rx_kind, traffic) = match net_interface {
+        Some(name) => (
+            HistogramKind::NetIfaceTx(name.to_string()),
+            HistogramKind::NetIfaceRx(name.to_string()),
+            // an interface that went away has no traffic
+            app.interfaces
+                .iter()
+                .find(|i| i.name == name)
+                .cloned()
+                .unwrap_or_default(),
+        ),
+        None => (
+            HistogramKind::NetTx,
+            HistogramKind::NetRx,
+            InterfaceTraffic {
+                rx_bytes: app.net_in,
+                tx_bytes: app.net_out,
+                ..app.net_total.sum(&app.interfaces)
+            },
+        ),
     };
-    let up_max_bytes = float_to_byte_string!(up_max as f64, Unit::B);
 
-    Sparkline::default()
+    let arrow = |selected: bool| if selected { "→" } else { " " };
+    let mut items = vec![ListItem::new(Span::styled(
+        Cow::Owned(format!(
+            "{}Total [{}]",
+            arrow(net_interface.is_none()),
+            app.net_total
+        )),
+        ok_style(),
+    ))];
+    items.extend(app.interfaces.iter().map(|i| {
+        let ip = app
+            .network_interfaces
+            .iter()
+            .find(|n| n.name == i.name)
+            .map(|n| n.ip.as_str())
+            .unwrap_or_default();
+        // virtual interfaces aren't counted in the physical total
+        let style = if i.is_virtual {
+            Style::default()
+        } else {
+            ok_style()
+        };
+        ListItem::new(Span::styled(
+            Cow::Owned(format!(
+                "{}{:<10.10} {}",
+                arrow(net_interface == Some(i.name.as_str())),
+                i.name,
+                ip
+            )),
+            style,
+        ))
+    }));
+    List::new(items)
         .block(
             Block::default()
-                .title(format!("↑ [{net_up:^10}/s] PEAK [{up_max_bytes:^10}/s]").as_str()),
+                .title(Span::styled("Interfaces", border_style))
+                .borders(Borders::ALL)
+                .border_style(border_style),
         )
-        .data(h_out.data())
-        .style(Style::default().fg(Color::LightYellow))
-        .max(up_max)
-        .render(f, net[0]);
+        .render(f, network_layout[0]);
 
-    let net_down = float_to_byte_string!(
-        app.net_in as f64 / app.histogram_map.tick.as_secs_f64(),
-        Unit::B
-    );
-    let h_in = match app.histogram_map.get_zoomed(&HistogramKind::NetRx, &view) {
-        Some(h) => h,
-        None => return,
+    let packets = |n| net_interface.map(|_| n);
+    let mut tx_counters = vec![("ERR", traffic.tx_errors), ("DROP", traffic.tx_drops)];
+    let mut rx_counters = vec![("ERR", traffic.rx_errors), ("DROP", traffic.rx_drops)];
+    if let Some(tcp) = app.tcp {
+        tx_counters.push(("RETRANS", tcp.retransmits));
+        rx_counters.push(("RST", tcp.resets));
+        rx_counters.push(("LISTEN OVF", tcp.listen_overflows));
+    }
+    // the traffic of processes is only known summed over the interfaces
+    #[cfg(target_os = "linux")]
+    let (top_tx, top_rx) = match net_interface {
+        Some(_) => (None, None),
+        None => (
+            Some(top_talker(
+                app,
+                &view,
+                TopMetric::NetTx,
+                app.top_pids.net_tx.pid,
+            )),
+            Some(top_talker(
+                app,
+                &view,
+                TopMetric::NetRx,
+                app.top_pids.net_rx.pid,
+            )),
+        ),
     };
+    #[cfg(not(target_os = "linux"))]
+    let (top_tx, top_rx) = (None, None);
+    traffic_chart(
+        app,
+        &tx_kind,
+        &view,
+        "↑",
+        traffic.tx_bytes,
+        packets(traffic.tx_packets),
+        &tx_counters,
+        top_tx,
+        Color::LightYellow,
+        net[0],
+        f,
+    );
+    traffic_chart(
+        app,
+        &rx_kind,
+        &view,
+        "↓",
+        traffic.rx_bytes,
+        packets(traffic.rx_packets),
+        &rx_counters,
+        top_rx,
+        Color::LightMagenta,
+        net[1],
+        f,
+    );
+}
 
-    let down_max: u64 = match h_in.data().iter().max() {
-        Some(x) => *x,
-        None => 1,
-    };
-    let down_max_bytes = float_to_byte_string!(down_max as f64, Unit::B);
-    Sparkline::default()
-        .block(
-            Block::default()
-                .title(format!("↓ [{net_down:^10}/s] PEAK [{down_max_bytes:^10}/s]").as_str()),
-        )
-        .data(h_in.data())
-        .style(Style::default().fg(Color::LightMagenta))
-        .max(down_max)
-        .render(f, net[1]);
+/// The process sending or receiving the most over TCP, `pid` in the present or those recorded
+/// for the newest column shown when scrolled back.
+#[cfg(target_os = "linux")]
+fn top_talker(app: &CPUTimeApp, view: &View, metric: TopMetric, pid: Option<u32>) -> String {
+    let rate = |v: f32| format!("{}/s", float_to_byte_string!(v as f64, Unit::B));
+    top_at_offset(app, view, metric, rate).unwrap_or_else(|| {
+        match pid.and_then(|pid| app.process_map.get(&pid)) {
+            Some(p) => format!("TOP [{:} - {:} - {:}]", p.pid, p.name, p.user_name),
+            None => String::from(""),
+        }
+    })
+}
 
-    let ips: Vec<_> = app
-        .network_interfaces
-        .iter()
-        .map(|n| {
-            Span::styled(
-                Cow::Owned(format!("{:<8.8} : {}", n.name, n.ip)),
-                Style::default().fg(Color::Green),
-            )
-        })
-        .map(ListItem::new)
-        .collect();
-    List::new(ips)
-        .block(
-            Block::default()
-                .title(Span::styled("Network", border_style))
-                .borders(Borders::ALL)
-                .border_style(border_style),
-        )
-        .render(f, network_layout[0]);
+/// Chart of the bytes per tick in `kind`, titled with the bytes, packets and other counters of
+/// the last tick as rates and the processes moving the most. Counters that went up are
+/// highlighted.
+#[allow(clippy::too_many_arguments)]
+fn traffic_chart(
+    app: &CPUTimeApp,
+    kind: &HistogramKind,
+    view: &View,
+    arrow: &str,
+    bytes: u64,
+    packets: Option<u64>,
+    counters: &[(&str, u64)],
+    top: Option<String>,
+    color: Color,
+    area: Rect,
+    f: &mut Frame<'_>,
+) {
+    let Some(h) = app.histogram_map.get_zoomed(kind, view) else {
+        return;
+    };
+    let secs = app.histogram_map.tick.as_secs_f64();
+    let current = float_to_byte_string!(bytes as f64 / secs, Unit::B);
+    let max: u64 = h.recorded().max().unwrap_or(1);
+    let peak = float_to_byte_string!(max as f64 / secs, Unit::B);
+    let mut title = vec![Span::raw(format!("{arrow} [{current:^10}/s]"))];
+    if let Some(packets) = packets {
+        title.push(Span::raw(format!(
+            " [{:^8.0} pkt/s]",
+            packets as f64 / secs
+        )));
+    }
+    title.push(Span::raw(format!(" PEAK [{peak:^10}/s]")));
+    for (name, n) in counters {
+        title.push(Span::raw(format!(" {name} [")));
+        title.push(Span::styled(
+            format!("{:.1}/s", *n as f64 / secs),
+            if *n > 0 { max_style() } else { ok_style() },
+        ));
+        title.push(Span::raw("]"));
+    }
+    if let Some(top) = top.filter(|t| !t.is_empty()) {
+        title.push(Span::raw(format!(" {top}")));
+    }
+    history_sparkline(&h)
+        .block(Block::default().title(Line::from(title)))
+        .style(Style::default().fg(color))
+        .max(max)
+        .render(f, area);
+    render_cursor(&h, area, f, view);
 }
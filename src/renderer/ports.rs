@@ -0,0 +1,118 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+use super::style::max_style;
+use super::Render;
+use crate::metrics::CPUTimeApp;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+/// Popup over the middle of `area` listing the listening ports with the process holding each,
+/// `state` picks the port whose process is shown on return.
+pub fn render_ports(
+    app: &CPUTimeApp,
+    state: &mut TableState,
+    replaying: bool,
+    area: Rect,
+    f: &mut Frame<'_>,
+) {
+    let ports = &app.listening_ports;
+    let width = area.width.min(110);
+    let height = area.height.min(ports.len().max(1) as u16 + 5);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let header_style = Style::default().fg(Color::Green);
+    Clear.render(f, popup);
+    Block::default()
+        .title(Span::styled(
+            format!(" Listening Ports [{}] ", ports.len()),
+            header_style,
+        ))
+        .borders(Borders::ALL)
+        .render(f, popup);
+    let layout = Layout::default()
+        .margin(1)
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(popup);
+
+    let message = if replaying {
+        Some("Ports aren't recorded.")
+    } else if cfg!(not(target_os = "linux")) {
+        Some("Listening ports are only listed on Linux.")
+    } else if ports.is_empty() {
+        Some("No listening ports.")
+    } else {
+        None
+    };
+    if let Some(message) = message {
+        Paragraph::new(format!(" {message}")).render(f, layout[0]);
+        Paragraph::new(Span::styled(" Close [ESC]", header_style)).render(f, layout[1]);
+        return;
+    }
+
+    let rows = ports.iter().map(|port| {
+        let (pid, user, name) = match port.pids.first() {
+            Some(pid) => {
+                let (user, name) = match app.process_map.get(pid) {
+                    Some(p) => (p.user_name.clone(), p.name.clone()),
+                    None => (String::new(), String::from("?")),
+                };
+                // shared with its children, as by pre-forking servers
+                let name = match port.pids.len() {
+                    1 => name,
+                    n => format!("{name} (+{})", n - 1),
+                };
+                (format!("{pid:>7}"), user, name)
+            }
+            None => (
+                format!("{:>7}", "-"),
+                String::new(),
+                String::from("not visible"),
+            ),
+        };
+        Row::new(vec![
+            port.protocol.to_string(),
+            port.local.to_string(),
+            pid,
+            user,
+            name,
+        ])
+    });
+    let widths = [
+        Constraint::Length(6),
+        Constraint::Length(42),
+        Constraint::Length(9),
+        Constraint::Length(12),
+        Constraint::Fill(1),
+    ];
+    let table = Table::new(rows, widths)
+        .header(
+            Row::new(vec!["PROTO", "ADDRESS", "    PID", "USER", "PROCESS"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::Gray)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_stateful_widget(table, layout[0], state);
+
+    let footer = match ports.get(state.selected().unwrap_or(0)) {
+        Some(port) if port.pids.is_empty() => Span::styled(
+            " Its process isn't visible, e.g. another user's without root  Close [ESC]",
+            max_style(),
+        ),
+        _ => Span::styled(" Show process [RET] Close [ESC]", header_style),
+    };
+    Paragraph::new(footer).render(f, layout[1]);
+}
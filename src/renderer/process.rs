@@ -2,10 +2,13 @@
  * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
  */
 
+use super::style::{max_style, ok_style};
 use super::{percent_of, Render};
 use crate::float_to_byte_string;
+use crate::metrics::snapshots::ProcessView;
+use crate::metrics::sockets::{Socket, SocketState};
 use crate::metrics::zprocess::{ProcessStatusExt, ZProcess};
-use crate::metrics::{CPUTimeApp, ProcessTableSortOrder};
+use crate::metrics::{CPUTimeApp, ProcessTableSortOrder, Top};
 use byte_unit::{Byte, Unit};
 use chrono::prelude::DateTime;
 use chrono::Local;
@@ -15,11 +18,21 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap};
 use ratatui::Frame;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::time::{Duration, UNIX_EPOCH};
 
+/// Which processes the table lists while the charts may show the past.
+pub enum ProcessesShown<'a> {
+    Live,
+    /// The charts show the past but no snapshot goes back that far.
+    NoSnapshot,
+    Past(&'a ProcessView),
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn render_process_table(
     app: &CPUTimeApp,
+    shown: &ProcessesShown<'_>,
     process_table: &[u32],
     area: Rect,
     process_table_start: usize,
@@ -36,13 +49,25 @@ pub fn render_process_table(
         v => v as usize,
     };
 
+    let (process_map, top_pids, tasks, threads, cum_cpu) = match shown {
+        ProcessesShown::Past(past) => (
+            &past.process_map,
+            &past.top_pids,
+            past.processes.len(),
+            past.threads_total,
+            None,
+        ),
+        _ => (
+            &app.process_map,
+            &app.top_pids,
+            app.processes.len(),
+            app.threads_total,
+            app.cum_cpu_process.as_ref(),
+        ),
+    };
     let procs: Vec<&ZProcess> = process_table
         .iter()
-        .map(|pid| {
-            app.process_map
-                .get(pid)
-                .expect("expected pid to be present")
-        })
+        .map(|pid| process_map.get(pid).expect("expected pid to be present"))
         .collect();
     let highlighted_process = if !procs.is_empty() {
         Some(Box::new(procs[highlighted_row].clone()))
@@ -55,6 +80,8 @@ pub fn render_process_table(
 
     let rows: Vec<Row> = render_rows(
         app,
+        top_pids,
+        cum_cpu,
         procs,
         process_table_start,
         display_height,
@@ -77,6 +104,10 @@ pub fn render_process_table(
     ];
     #[cfg(target_os = "linux")]
     header.push(String::from("IOWAIT% "));
+    #[cfg(target_os = "linux")]
+    header.push(String::from("NET RX/s "));
+    #[cfg(target_os = "linux")]
+    header.push(String::from("NET TX/s "));
     #[cfg(feature = "nvidia")]
     header.push(String::from("GPU% "));
     #[cfg(feature = "nvidia")]
@@ -123,17 +154,40 @@ pub fn render_process_table(
     } else {
         format!(
             "Tasks [{:}] Threads [{:}]  Navigate [↑/↓] Sort Col [,/.] Asc/Dec [;] Filter [/]",
-            app.processes.len(),
-            app.threads_total
+            tasks, threads
         )
     };
+    let mut title = vec![Span::styled(title, border_style)];
+    match shown {
+        ProcessesShown::Past(past) => title.insert(
+            0,
+            Span::styled(
+                format!(
+                    " HISTORICAL {} ",
+                    DateTime::<Local>::from(past.time).format("%Y-%m-%d %H:%M:%S")
+                ),
+                Style::default()
+                    .bg(Color::Yellow)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ),
+        ProcessesShown::NoSnapshot => title.insert(
+            0,
+            Span::styled(
+                " No snapshot this far back, showing live processes ",
+                Style::default().fg(Color::Yellow),
+            ),
+        ),
+        ProcessesShown::Live => {}
+    }
 
     Table::new(rows, widths)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(border_style)
-                .title(Span::styled(title, border_style)),
+                .title(Line::from(title)),
         )
         .column_spacing(0)
         .header(
@@ -145,8 +199,11 @@ pub fn render_process_table(
     highlighted_process
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_rows<'a>(
     app: &CPUTimeApp,
+    top_pids: &Top,
+    cum_cpu: Option<&ZProcess>,
     procs: Vec<&'a ZProcess>,
     process_table_start: usize,
     display_height: usize,
@@ -173,8 +230,8 @@ fn render_rows<'a>(
                 String::from("")
             };
             let mut cpu_usage =
-                set_process_row_style(p.pid, app.top_pids.cpu.pid, format!("{:>5.1}", p.cpu_usage));
-            if let Some(top) = &app.cum_cpu_process {
+                set_process_row_style(p.pid, top_pids.cpu.pid, format!("{:>5.1}", p.cpu_usage));
+            if let Some(top) = cum_cpu {
                 if top.pid == p.pid {
                     cpu_usage = cpu_usage.style(Style::default().fg(Color::Magenta));
                 }
@@ -188,12 +245,12 @@ fn render_rows<'a>(
                 cpu_usage,
                 set_process_row_style(
                     p.pid,
-                    app.top_pids.mem.pid,
+                    top_pids.mem.pid,
                     format!("{:>5.1}", percent_of(p.memory, app.mem_total)),
                 ),
                 set_process_row_style(
                     p.pid,
-                    app.top_pids.mem.pid,
+                    top_pids.mem.pid,
                     format!(
                         "{:>8}",
                         float_to_byte_string!(p.memory as f64, Unit::B).replace('B', "")
@@ -201,7 +258,7 @@ fn render_rows<'a>(
                 ),
                 set_process_row_style(
                     p.pid,
-                    app.top_pids.virt.pid,
+                    top_pids.virt.pid,
                     format!(
                         "{:>8}",
                         float_to_byte_string!(p.virtual_memory as f64, Unit::B).replace('B', "")
@@ -210,7 +267,7 @@ fn render_rows<'a>(
                 Cell::from(format!("{:1}", p.status.to_single_char())),
                 set_process_row_style(
                     p.pid,
-                    app.top_pids.read.pid,
+                    top_pids.read.pid,
                     format!(
                         "{:>8}",
                         float_to_byte_string!(
@@ -222,7 +279,7 @@ fn render_rows<'a>(
                 ),
                 set_process_row_style(
                     p.pid,
-                    app.top_pids.write.pid,
+                    top_pids.write.pid,
                     format!(
                         "{:>8}",
                         float_to_byte_string!(
@@ -237,19 +294,39 @@ fn render_rows<'a>(
             #[cfg(target_os = "linux")]
             row.push(set_process_row_style(
                 p.pid,
-                app.top_pids.iowait.pid,
+                top_pids.iowait.pid,
                 format!("{:>5.1}", p.get_io_wait(&app.histogram_map.tick)),
             ));
+            #[cfg(target_os = "linux")]
+            row.push(set_process_row_style(
+                p.pid,
+                top_pids.net_rx.pid,
+                format!(
+                    "{:>8}",
+                    float_to_byte_string!(p.get_net_rx_bytes_sec(&app.histogram_map.tick), Unit::B)
+                        .replace('B', "")
+                ),
+            ));
+            #[cfg(target_os = "linux")]
+            row.push(set_process_row_style(
+                p.pid,
+                top_pids.net_tx.pid,
+                format!(
+                    "{:>8}",
+                    float_to_byte_string!(p.get_net_tx_bytes_sec(&app.histogram_map.tick), Unit::B)
+                        .replace('B', "")
+                ),
+            ));
             #[cfg(feature = "nvidia")]
             row.push(set_process_row_style(
                 p.pid,
-                app.top_pids.gpu.pid,
+                top_pids.gpu.pid,
                 format!("{:>4.0}", p.gpu_usage),
             ));
             #[cfg(feature = "nvidia")]
             row.push(set_process_row_style(
                 p.pid,
-                app.top_pids.frame_buffer.pid,
+                top_pids.frame_buffer.pid,
                 format!("{:>4.0}", p.fb_utilization),
             ));
 
@@ -271,6 +348,8 @@ fn render_rows<'a>(
         .collect()
 }
 
+/// Details of `p` with its sockets beside them, listed from row `socket_row_start`. Returns how
+/// many sockets fit.
 pub fn render_process(
     app: &CPUTimeApp,
     layout: Rect,
@@ -278,7 +357,8 @@ pub fn render_process(
     border_style: Style,
     process_message: &Option<String>,
     p: &ZProcess,
-) {
+    socket_row_start: usize,
+) -> u16 {
     Block::default()
         .title(Span::styled(format!("Process: {0}", p.name), border_style))
         .borders(Borders::ALL)
@@ -302,13 +382,12 @@ pub fn render_process(
 
     //Block::default().borders(Borders::LEFT).render(f, h_sections[1]);
 
-    let alive = if p.end_time.is_some() {
-        format!(
+    let alive = match p.end_time {
+        Some(end_time) => format!(
             "dead since {:}",
-            DateTime::<Local>::from(UNIX_EPOCH + Duration::from_secs(p.end_time.unwrap()))
-        )
-    } else {
-        "alive".to_string()
+            DateTime::<Local>::from(UNIX_EPOCH + Duration::from_secs(end_time))
+        ),
+        None => "alive".to_string(),
     };
     let run_duration = p.get_run_duration();
     let d = format!(
@@ -482,8 +561,51 @@ pub fn render_process(
             rhs_style,
         ),
     ]));
+    #[cfg(target_os = "linux")]
+    text.push(Line::from(vec![
+        Span::raw("Net Received:          "),
+        Span::styled(
+            format!(
+                "{:>10}/s  (Peak: {:}/s)",
+                float_to_byte_string!(p.get_net_rx_bytes_sec(&app.histogram_map.tick), Unit::B),
+                float_to_byte_string!(p.peak_net_rx_bytes_sec, Unit::B)
+            ),
+            rhs_style,
+        ),
+    ]));
+    #[cfg(target_os = "linux")]
+    text.push(Line::from(vec![
+        Span::raw("Net Sent:              "),
+        Span::styled(
+            format!(
+                "{:>10}/s  (Peak: {:}/s)",
+                float_to_byte_string!(p.get_net_tx_bytes_sec(&app.histogram_map.tick), Unit::B),
+                float_to_byte_string!(p.peak_net_tx_bytes_sec, Unit::B)
+            ),
+            rhs_style,
+        ),
+    ]));
+
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(50),
+                Constraint::Length(1),
+                Constraint::Percentage(50),
+            ]
+            .as_ref(),
+        )
+        .split(v_sections[1]);
+    let socket_rows = render_sockets(
+        &app.process_sockets,
+        socket_row_start,
+        body[2],
+        f,
+        border_style,
+    );
 
-    if text.len() > v_sections[1].height as usize * 3 {
+    if text.len() > body[0].height as usize * 3 {
         let h_sections = Layout::default()
             .direction(Direction::Horizontal)
             .margin(0)
@@ -495,7 +617,7 @@ pub fn render_process(
                 ]
                 .as_ref(),
             )
-            .split(v_sections[1]);
+            .split(body[0]);
 
         let second_part = text.split_off(h_sections[0].height as usize * 3);
         Paragraph::new(text)
@@ -511,22 +633,105 @@ pub fn render_process(
         Paragraph::new(text)
             .block(Block::default())
             .wrap(Wrap { trim: true })
-            .render(f, v_sections[1]);
+            .render(f, body[0]);
     }
+    socket_rows
 }
 
-pub fn filter_process_table<'a>(app: &'a CPUTimeApp, filter: &str) -> Cow<'a, [u32]> {
+/// Table of the sockets of a process from row `start`, returning how many rows fit.
+fn render_sockets(
+    sockets: &Result<Vec<Socket>, String>,
+    start: usize,
+    area: Rect,
+    f: &mut Frame<'_>,
+    border_style: Style,
+) -> u16 {
+    let title = match sockets {
+        Ok(sockets) if !sockets.is_empty() => format!(
+            "Sockets [{}-{}/{}] Scroll [↑/↓]",
+            start + 1,
+            (start + area.height.saturating_sub(3) as usize).min(sockets.len()),
+            sockets.len()
+        ),
+        _ => String::from("Sockets"),
+    };
+    let block = Block::default()
+        .title(Span::styled(title, border_style))
+        .borders(Borders::ALL)
+        .border_style(border_style);
+    let sockets = match sockets {
+        Ok(sockets) if sockets.is_empty() => {
+            Paragraph::new(" No sockets open.")
+                .block(block)
+                .render(f, area);
+            return 0;
+        }
+        Ok(sockets) => sockets,
+        Err(e) => {
+            Paragraph::new(Span::styled(format!(" {e}"), max_style()))
+                .block(block)
+                .wrap(Wrap { trim: false })
+                .render(f, area);
+            return 0;
+        }
+    };
+    // borders and header
+    let height = area.height.saturating_sub(3);
+    let queue = |q: Option<u32>| match q {
+        // data piling up in a queue
+        Some(q) if q > 0 => Cell::from(format!("{q:>7}")).style(max_style()),
+        Some(q) => Cell::from(format!("{q:>7}")),
+        None => Cell::from(format!("{:>7}", "-")),
+    };
+    let rows = sockets.iter().skip(start).take(height as usize).map(|s| {
+        let state = Cell::from(s.state.to_string());
+        Row::new(vec![
+            Cell::from(s.protocol.to_string()),
+            if s.state == SocketState::Listen {
+                state.style(ok_style())
+            } else {
+                state
+            },
+            queue(s.recv_queue),
+            queue(s.send_queue),
+            Cell::from(s.local.to_string()),
+            Cell::from(s.remote.as_ref().map(|r| r.to_string()).unwrap_or_default()),
+        ])
+    });
+    let widths = [
+        Constraint::Length(6),
+        Constraint::Length(11),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Fill(1),
+        Constraint::Fill(1),
+    ];
+    Table::new(rows, widths)
+        .block(block)
+        .header(
+            Row::new(vec![
+                "PROTO", "STATE", " RECV-Q", " SEND-Q", "LOCAL", "REMOTE",
+            ])
+            .style(Style::default().bg(Color::DarkGray)),
+        )
+        .render(f, area);
+    height
+}
+
+pub fn filter_process_table<'a>(
+    process_map: &HashMap<u32, ZProcess>,
+    processes: &'a [u32],
+    filter: &str,
+) -> Cow<'a, [u32]> {
     if filter.is_empty() {
-        return Cow::Borrowed(&app.processes);
+        return Cow::Borrowed(processes);
     }
 
     let filter_lc = filter.to_lowercase();
-    let results: Vec<u32> = app
-        .processes
+    let results: Vec<u32> = processes
         .iter()
         .filter(|pid| {
-            let p = app
-                .process_map
+            let p = process_map
                 .get(pid)
                 .expect("Pid present in processes but not in map.");
             p.name.to_lowercase().contains(&filter_lc)
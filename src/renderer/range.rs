@@ -0,0 +1,345 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! The cursor on the charts: the values under it and statistics of a range picked with it.
+
+use super::section::Section;
+use super::style::{no_data_style, ok_style};
+use super::{Render, LEFT_PANE_WIDTH};
+use crate::float_to_byte_string;
+use crate::metrics::histogram::{HistogramKind, View};
+use crate::metrics::CPUTimeApp;
+use byte_unit::{Byte, Unit};
+use chrono::{DateTime, Local};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::time::{Duration, SystemTime};
+
+/// A chart of a section and how its values read.
+pub struct Series {
+    pub name: String,
+    pub kind: HistogramKind,
+    pub format: fn(f64, &Duration) -> String,
+}
+
+fn percent(v: f64, _tick: &Duration) -> String {
+    format!("{v:.1}%")
+}
+
+fn bytes(v: f64, _tick: &Duration) -> String {
+    float_to_byte_string!(v, Unit::B)
+}
+
+fn bytes_per_sec(v: f64, _tick: &Duration) -> String {
+    format!("{}/s", float_to_byte_string!(v, Unit::B))
+}
+
+/// Network charts hold the bytes transferred during a tick.
+fn bytes_per_tick(v: f64, tick: &Duration) -> String {
+    bytes_per_sec(v / tick.as_secs_f64(), tick)
+}
+
+fn packets_per_tick(v: f64, tick: &Duration) -> String {
+    format!("{:.0}/s", v / tick.as_secs_f64())
+}
+
+/// Errors, drops and other events that are rare enough to be shown with a fraction.
+fn events_per_tick(v: f64, tick: &Duration) -> String {
+    format!("{:.1}/s", v / tick.as_secs_f64())
+}
+
+/// The charts shown in `section`, for the file system, interface and graphics device picked
+/// there.
+pub fn section_series(
+    app: &CPUTimeApp,
+    section: Section,
+    file_system_index: usize,
+    gfx_device_index: usize,
+    net_interface: Option<&str>,
+) -> Vec<Series> {
+    let series = |name: &str, kind: HistogramKind, format: fn(f64, &Duration) -> String| Series {
+        name: name.to_string(),
+        kind,
+        format,
+    };
+    match section {
+        Section::Cpu => vec![
+            series("CPU", HistogramKind::Cpu, percent),
+            series("MEM", HistogramKind::Mem, percent),
+        ],
+        Section::Network => {
+            let mut network = match net_interface {
+                Some(iface) => vec![
+                    series(
+                        &format!("↑ TX {}", iface),
+                        HistogramKind::NetIfaceTx(iface.to_string()),
+                        bytes_per_tick,
+                    ),
+                    series(
+                        &format!("↓ RX {}", iface),
+                        HistogramKind::NetIfaceRx(iface.to_string()),
+                        bytes_per_tick,
+                    ),
+                    series(
+                        &format!("↑ PKTS {}", iface),
+                        HistogramKind::NetIfacePacketsTx(iface.to_string()),
+                        packets_per_tick,
+                    ),
+                    series(
+                        &format!("↓ PKTS {}", iface),
+                        HistogramKind::NetIfacePacketsRx(iface.to_string()),
+                        packets_per_tick,
+                    ),
+                    series(
+                        &format!("↑ ERR {}", iface),
+                        HistogramKind::NetIfaceErrorsTx(iface.to_string()),
+                        events_per_tick,
+                    ),
+                    series(
+                        &format!("↓ ERR {}", iface),
+                        HistogramKind::NetIfaceErrorsRx(iface.to_string()),
+                        events_per_tick,
+                    ),
+                    series(
+                        &format!("↑ DROP {}", iface),
+                        HistogramKind::NetIfaceDropsTx(iface.to_string()),
+                        events_per_tick,
+                    ),
+                    series(
+                        &format!("↓ DROP {}", iface),
+                        HistogramKind::NetIfaceDropsRx(iface.to_string()),
+                        events_per_tick,
+                    ),
+                ],
+                None => vec![
+                    series("↑ TX", HistogramKind::NetTx, bytes_per_tick),
+                    series("↓ RX", HistogramKind::NetRx, bytes_per_tick),
+                ],
+            };
+            if app.tcp.is_some() {
+                network.extend([
+                    series("RETRANS", HistogramKind::TcpRetransmits, events_per_tick),
+                    series("RST", HistogramKind::TcpResets, events_per_tick),
+                    series(
+                        "LISTEN OVF",
+                        HistogramKind::TcpListenOverflows,
+                        events_per_tick,
+                    ),
+                ]);
+            }
+            network
+        }
+        Section::Disk => {
+            let mut disk_list: Vec<_> = app.disks.values().collect();
+            disk_list.sort_by(|a, b| b.mount_point.cmp(&a.mount_point));
+            match disk_list.get(file_system_index) {
+                Some(fs) => vec![
+                    series(
+                        &format!("R {}", fs.name),
+                        HistogramKind::IoRead(fs.name.to_string()),
+                        bytes_per_sec,
+                    ),
+                    series(
+                        &format!("W {}", fs.name),
+                        HistogramKind::IoWrite(fs.name.to_string()),
+                        bytes_per_sec,
+                    ),
+                    series(
+                        &format!("USED {}", fs.name),
+                        HistogramKind::FileSystemUsedSpace(fs.name.to_string()),
+                        bytes,
+                    ),
+                ],
+                None => vec![],
+            }
+        }
+        Section::Graphics => match app.gfx_devices.get(gfx_device_index) {
+            Some(gd) => vec![
+                series(
+                    &format!("GPU {}", gd.name),
+                    HistogramKind::GpuUse(gd.uuid.clone()),
+                    percent,
+                ),
+                series(
+                    &format!("FB {}", gd.name),
+                    HistogramKind::GpuMem(gd.uuid.clone()),
+                    percent,
+                ),
+            ],
+            None => vec![],
+        },
+        Section::Process => vec![],
+    }
+}
+
+/// Statistics of every chart of a section over a range of columns, formatted for the popup.
+pub struct RangeStats {
+    section: Section,
+    start: Option<SystemTime>,
+    end: Option<SystemTime>,
+    /// Name, then min, max, mean, p50, p95 and p99, or None without samples in the range.
+    rows: Vec<(String, Option<[String; 6]>)>,
+}
+
+impl RangeStats {
+    /// Columns `newest` to `oldest` of the charts of `view`, as counted by `View::cursor`.
+    pub fn new(
+        app: &CPUTimeApp,
+        section: Section,
+        series: &[Series],
+        view: &View,
+        newest: usize,
+        oldest: usize,
+    ) -> RangeStats {
+        let hm = &app.histogram_map;
+        let zf = view.zoom_factor.max(1) as usize;
+        let rows = series
+            .iter()
+            .map(|s| {
+                let stats = hm.column_stats(&s.kind, view, newest, oldest);
+                let format = |v: f64| (s.format)(v, &hm.tick);
+                let values = stats.map(|st| {
+                    [
+                        format(st.min as f64),
+                        format(st.max as f64),
+                        format(st.mean),
+                        format(st.p50 as f64),
+                        format(st.p95 as f64),
+                        format(st.p99 as f64),
+                    ]
+                });
+                (s.name.clone(), values)
+            })
+            .collect();
+        RangeStats {
+            section,
+            start: hm.time_at(hm.column_age(view, oldest) + zf - 1),
+            end: hm.time_at(hm.column_age(view, newest)),
+            rows,
+        }
+    }
+}
+
+fn timestamp(t: Option<SystemTime>) -> String {
+    match t {
+        Some(t) => DateTime::<Local>::from(t)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => String::from("?"),
+    }
+}
+
+/// Time and values of the charts at the cursor, on the bottom border of the section in `area`.
+/// Zoomed out, a column is the average of the ticks it covers.
+pub fn render_cursor_values(
+    app: &CPUTimeApp,
+    series: &[Series],
+    view: &View,
+    column: usize,
+    area: Rect,
+    f: &mut Frame<'_>,
+) {
+    if area.height == 0 || area.width <= LEFT_PANE_WIDTH + 2 {
+        return;
+    }
+    let hm = &app.histogram_map;
+    let mut spans = vec![
+        Span::raw(" AT ["),
+        Span::styled(
+            timestamp(hm.time_at(hm.column_age(view, column))),
+            Style::default().fg(Color::Green),
+        ),
+        Span::raw("]"),
+    ];
+    if view.zoom_factor > 1 {
+        spans.push(Span::raw(format!(" AVG OF {} TICKS", view.zoom_factor)));
+    }
+    for s in series {
+        let value = hm
+            .get_columns(&s.kind, view, column, column)
+            .and_then(|h| h.points().next().flatten());
+        spans.push(Span::raw(format!(" {} [", s.name)));
+        spans.push(match value {
+            Some(v) => Span::styled((s.format)(v as f64, &hm.tick), ok_style()),
+            None => Span::styled("no data", no_data_style()),
+        });
+        spans.push(Span::raw("]"));
+    }
+    spans.push(Span::raw(" "));
+    let readout = Rect {
+        x: area.x + LEFT_PANE_WIDTH + 1,
+        y: area.y + area.height - 1,
+        width: area.width - LEFT_PANE_WIDTH - 2,
+        height: 1,
+    };
+    Paragraph::new(Line::from(spans)).render(f, readout);
+}
+
+/// Popup over the middle of `area`.
+pub fn render_range_stats(stats: &RangeStats, area: Rect, f: &mut Frame<'_>) {
+    let width = area.width.min(100);
+    let height = area.height.min(stats.rows.len().max(1) as u16 + 7);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let header_style = Style::default().fg(Color::Green);
+    Clear.render(f, popup);
+    Block::default()
+        .title(Span::styled(
+            format!("{} Range ", stats.section),
+            header_style,
+        ))
+        .borders(Borders::ALL)
+        .render(f, popup);
+    let layout = Layout::default()
+        .margin(1)
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(popup);
+
+    Paragraph::new(Line::from(vec![
+        Span::raw(" FROM ["),
+        Span::styled(timestamp(stats.start), header_style),
+        Span::raw("] TO ["),
+        Span::styled(timestamp(stats.end), header_style),
+        Span::raw("]"),
+    ]))
+    .render(f, layout[0]);
+
+    if stats.rows.is_empty() {
+        Paragraph::new(" No charts in this section.").render(f, layout[1]);
+    } else {
+        let rows = stats.rows.iter().map(|(name, values)| {
+            let mut cells = vec![name.clone()];
+            match values {
+                Some(values) => cells.extend(values.iter().cloned()),
+                None => cells.push(String::from("no samples")),
+            }
+            Row::new(cells)
+        });
+        let mut widths = vec![Constraint::Min(12)];
+        widths.extend([Constraint::Length(12); 6].iter());
+        Table::new(rows, widths)
+            .header(
+                Row::new(vec!["", "MIN", "MAX", "MEAN", "P50", "P95", "P99"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .render(f, layout[1]);
+    }
+
+    Paragraph::new(Span::styled(" Close [any key]", header_style)).render(f, layout[2]);
+}
@@ -10,3 +10,18 @@ pub fn max_style() -> Style {
 pub fn ok_style() -> Style {
     Style::default().fg(OK_COLOR)
 }
+
+/// Style of the ticks in a chart that have no recorded sample.
+pub fn no_data_style() -> Style {
+    Style::default().fg(Color::DarkGray)
+}
+
+/// Column of the charts under the cursor.
+pub fn cursor_style() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+/// Columns of the range being selected on the charts.
+pub fn range_style() -> Style {
+    Style::default().bg(Color::DarkGray)
+}
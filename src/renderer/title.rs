@@ -2,7 +2,7 @@
  * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
  */
 
-use super::Render;
+use super::{chart_width, Render};
 use crate::metrics::*;
 use chrono::prelude::DateTime;
 use chrono::Duration as CDuration;
@@ -44,6 +44,16 @@ fn display_time(start: DateTime<Local>, end: DateTime<Local>) -> String {
     )
 }
 
+fn display_span(d: CDuration) -> String {
+    if d.num_hours() >= 48 {
+        format!("{:} days", d.num_days())
+    } else if d.num_minutes() >= 120 {
+        format!("{:} hrs", d.num_hours())
+    } else {
+        format!("{:} mins", d.num_minutes())
+    }
+}
+
 fn render_battery_widget(
     batteries: &[starship_battery::Battery],
 ) -> (Span<'_>, Span<'_>, Span<'_>, Span<'_>) {
@@ -116,11 +126,11 @@ pub fn render_top_title_bar(
     f: &mut Frame<'_>,
     zf: &u32,
     offset: &usize,
+    session_status: Option<&str>,
 ) {
-    let tick = app.histogram_map.tick;
-    let hist_duration = app.histogram_map.hist_duration(area.width as usize, *zf);
-    let offset_duration = chrono::Duration::from_std(tick.mul(*offset as u32).mul(*zf))
-        .expect("Couldn't convert from std");
+    let hm = &app.histogram_map;
+    let width = chart_width(area.width);
+    let zoom = *zf as usize;
     let uptime = match CDuration::from_std(app.uptime) {
         Ok(d) => format!(
             " [Up {:} days {:02}:{:02}:{:02}]",
@@ -131,13 +141,39 @@ pub fn render_top_title_bar(
         ),
         Err(_) => String::from(""),
     };
-    let now = Local::now();
-    let start = now
-        .checked_sub_signed(hist_duration + offset_duration)
-        .expect("Couldn't compute time");
-    let end = now
-        .checked_sub_signed(offset_duration)
-        .expect("Couldn't add time");
+    // capture times of the newest sample and of the first and last one on screen
+    let (start, end, offset_duration) = match (
+        hm.time_at(0),
+        hm.time_at(offset * zoom),
+        hm.time_at((offset + width) * zoom),
+    ) {
+        (Some(newest), Some(end), Some(start)) => {
+            let end = DateTime::<Local>::from(end);
+            (
+                DateTime::<Local>::from(start),
+                end,
+                DateTime::<Local>::from(newest) - end,
+            )
+        }
+        _ => {
+            let offset_duration = chrono::Duration::from_std(hm.tick.mul(*offset as u32).mul(*zf))
+                .expect("Couldn't convert from std");
+            let hist_duration = hm.hist_duration(width, *zf);
+            let now = Local::now();
+            let start = now
+                .checked_sub_signed(hist_duration + offset_duration)
+                .expect("Couldn't compute time");
+            let end = now
+                .checked_sub_signed(offset_duration)
+                .expect("Couldn't add time");
+            (start, end, offset_duration)
+        }
+    };
+    let hist_duration = end - start;
+    let history = match hm.recorded_span().map(CDuration::from_std) {
+        Some(Ok(d)) => format!(" [History: {:}]", display_span(d)),
+        _ => String::from(""),
+    };
     let default_style = Style::default().bg(Color::DarkGray).fg(Color::White);
     let back_in_time = if offset_duration.num_seconds() > 0 {
         format!(
@@ -152,10 +188,21 @@ pub fn render_top_title_bar(
     let battery_widets = render_battery_widget(&app.batteries);
     let battery_start = if !app.batteries.is_empty() { " [" } else { "" };
     let battery_end = if !app.batteries.is_empty() { "]" } else { "" };
-    let not_recording_warning = if app.writes_db_store() {
-        ""
+    let not_recording_warning = if let Some(status) = session_status {
+        status.to_string()
+    } else if app.writes_db_store() {
+        String::from("")
+    } else if let Some(db) = app.histogram_map.viewing() {
+        match hm.previous_stop() {
+            Some(stop) => format!(
+                " Viewing {:} saved {:} ",
+                db.display(),
+                DateTime::<Local>::from(stop).format("%H:%M:%S")
+            ),
+            None => format!(" Viewing {:}, nothing recorded yet ", db.display()),
+        }
     } else {
-        " History not recording, more info: (h) "
+        String::from(" History not recording, more info: (h) ")
     };
 
     let mut line = vec![
@@ -175,13 +222,11 @@ pub fn render_top_title_bar(
         battery_widets.3,
         Span::styled(battery_end, default_style),
         Span::styled(" [Showing: ", default_style),
-        Span::styled(
-            format!("{:} mins", hist_duration.num_minutes()),
-            default_style.fg(Color::Green),
-        ),
+        Span::styled(display_span(hist_duration), default_style.fg(Color::Green)),
         Span::styled(display_time(start, end), default_style),
         Span::styled(back_in_time, default_style.add_modifier(Modifier::BOLD)),
         Span::styled("]", default_style),
+        Span::styled(history, default_style),
         Span::styled(" (h)elp", default_style),
         Span::styled(" (q)uit", default_style),
         Span::styled(" sect(i)ons", default_style),
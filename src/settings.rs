@@ -0,0 +1,104 @@
+/*!
+ * Copyright 2019-2026, Benjamin Vaisvil and the zenith contributors
+ */
+
+//! Settings file, `zenith.conf` in the user's config directory. Lines are `key = value`,
+//! `#` starts a comment. Command line options take precedence over it.
+
+use crate::metrics::network::NetTotal;
+use crate::util::parse_duration;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Settings {
+    pub history_retention: Option<Duration>,
+    pub memory_window: Option<Duration>,
+    pub process_snapshots: Option<usize>,
+    pub net_total: Option<NetTotal>,
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    dirs_next::config_dir().map(|d| d.join("zenith").join("zenith.conf"))
+}
+
+/// Reads the settings at `path`, a missing file has no settings.
+pub fn load(path: &Path) -> Result<Settings, String> {
+    match fs::read_to_string(path) {
+        Ok(s) => parse(&s).map_err(|e| format!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Settings::default()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+fn parse(s: &str) -> Result<Settings, String> {
+    let mut settings = Settings::default();
+    for (n, line) in s.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected key = value", n + 1))?;
+        let value = value.trim();
+        let err = |e: String| format!("line {}: {}", n + 1, e);
+        match key.trim() {
+            "history_retention" => {
+                settings.history_retention = Some(parse_duration(value).map_err(err)?)
+            }
+            "memory_window" => settings.memory_window = Some(parse_duration(value).map_err(err)?),
+            "process_snapshots" => {
+                settings.process_snapshots = Some(
+                    value
+                        .parse()
+                        .map_err(|_| err(format!("expected a count, got {}", value)))?,
+                )
+            }
+            "net_total" => settings.net_total = Some(value.parse().map_err(err)?),
+            k => return Err(format!("line {}: unknown setting {}", n + 1, k)),
+        }
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        let settings = parse(
+            "# small VM\n\
+             history_retention = 1h\n\
+             \n\
+             memory_window=30m # raw samples\n\
+             process_snapshots = 3600\n\
+             net_total = eth0, wlan0\n",
+        )
+        .unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                history_retention: Some(Duration::from_secs(60 * 60)),
+                memory_window: Some(Duration::from_secs(30 * 60)),
+                process_snapshots: Some(3600),
+                net_total: Some(NetTotal::Interfaces(vec![
+                    "eth0".to_string(),
+                    "wlan0".to_string()
+                ])),
+            }
+        );
+        assert_eq!(parse("").unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn test_parse_settings_errors() {
+        assert!(parse("history_retention").is_err());
+        assert!(parse("history_retention = forever").is_err());
+        assert!(parse("colour = blue").is_err());
+        assert!(parse("process_snapshots = -1").is_err());
+    }
+}
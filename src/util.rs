@@ -4,15 +4,19 @@
  */
 
 use crate::constants::DEFAULT_TICK;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
 use crossterm::{event, event::Event as CEvent, event::KeyCode as Key, event::KeyEvent};
 use signal_hook::consts::signal::{SIGABRT, SIGINT, SIGTERM};
 use signal_hook::iterator::Signals;
-use std::fs::{remove_file, File};
-use std::io::Write;
+use std::fmt;
+use std::fs::{remove_file, File, OpenOptions};
+use std::io;
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
 pub enum Event<I> {
     Input(I),
@@ -72,13 +76,26 @@ impl Events {
             thread::spawn(move || {
                 let tx = tx.clone();
                 let mut count: u64 = 0;
+                // ticks are scheduled against fixed deadlines so the time spent sending and
+                // updating doesn't add up to drift
+                let mut deadline = Instant::now();
                 loop {
                     tx.send(Event::Tick).expect("Couldn't send event.");
                     count += 1;
                     if count.is_multiple_of(60) {
                         tx.send(Event::Save).expect("Couldn't send event");
                     }
-                    thread::sleep(config.tick_rate);
+                    deadline += config.tick_rate;
+                    let now = Instant::now();
+                    if deadline > now {
+                        thread::sleep(deadline - now);
+                    } else {
+                        // suspended or far behind, skip the missed ticks instead of bursting
+                        while deadline <= now {
+                            deadline += config.tick_rate;
+                        }
+                        thread::sleep(deadline - now);
+                    }
                 }
             })
         };
@@ -107,40 +124,138 @@ impl Events {
     }
 }
 
-/// Keeps a file open exclusively
-/// Removes the file when dropped
+/// Name of this machine. Databases and locks record it so hosts sharing a home directory
+/// keep apart.
+pub fn hostname() -> String {
+    sysinfo::System::host_name().unwrap_or_else(|| "localhost".to_string())
+}
+
+/// The zenith a lockfile was written by.
+#[derive(Debug, PartialEq)]
+pub struct LockHolder {
+    pub pid: i32,
+    pub host: Option<String>,
+    /// Name of the executable, which differs from `zenith` for renamed installs.
+    pub command: Option<String>,
+    pub started: Option<String>,
+}
+
+impl fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (pid {})",
+            self.command.as_deref().unwrap_or("zenith"),
+            self.pid
+        )?;
+        if let Some(host) = &self.host {
+            write!(f, " on {}", host)?;
+        }
+        if let Some(started) = &self.started {
+            write!(f, " since {}", started)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum LockError {
+    /// Locked by another process. The holder is unknown if it hasn't written the file yet or
+    /// the file was written by something else.
+    Held(Option<LockHolder>),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LockError::Held(Some(holder)) => write!(f, "held by {}", holder),
+            LockError::Held(None) => write!(f, "held by another process"),
+            LockError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Holds an exclusive `flock` on a file for as long as it lives. The kernel releases the lock
+/// when the process exits, however it exits, so a crashed zenith never leaves a stale lock
+/// behind. The pid, host, executable and start time are written into the file to tell who
+/// holds it.
 pub struct Lockfile {
     file: File,
     path: PathBuf,
 }
 
 impl Lockfile {
-    /// Tries to open the file creating if it does not exist
-    /// Fails if zenith is already running using the same lockfile
-    pub async fn new(main_pid: u32, path: &Path) -> Option<Self> {
-        if is_zenith_running(path).await {
-            debug!("{}", path.to_string_lossy());
-            return None;
-        }
+    /// Opens the file, creating it if it does not exist, and locks it.
+    /// Fails if another process holds the lock.
+    pub fn new(main_pid: u32, path: &Path) -> Result<Self, LockError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(LockError::Io)?;
 
-        let mut file = File::create(path).ok()?;
+        if let Err(e) = try_lock(&file) {
+            if e.kind() != io::ErrorKind::WouldBlock {
+                return Err(LockError::Io(e));
+            }
+            debug!("{} is locked", path.display());
+            let mut data = String::new();
+            let holder = file
+                .read_to_string(&mut data)
+                .ok()
+                .and_then(|_| parse_lockfile(&data));
+            return Err(LockError::Held(holder));
+        }
 
-        file.write_all(main_pid.to_string().as_bytes()).ok()?;
+        let command = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "zenith".to_string());
+        let started = Local::now().format("%Y-%m-%d %H:%M:%S");
+        file.set_len(0)
+            .and_then(|_| {
+                file.write_all(
+                    format!("{}\n{}\n{}\n{}\n", main_pid, hostname(), command, started).as_bytes(),
+                )
+            })
+            .map_err(LockError::Io)?;
 
-        Some(Self {
+        Ok(Self {
             file,
             path: path.into(),
         })
     }
+
+    /// Removes the lock file and locks a new one in its place. A process still holding the old
+    /// file keeps its lock on a file nobody else opens, so only use this when the holder is
+    /// known to be gone or stuck, e.g. on a hung network filesystem.
+    pub fn force(main_pid: u32, path: &Path) -> Result<Self, LockError> {
+        let previous = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| parse_lockfile(&data));
+        match remove_file(path) {
+            Ok(()) => match previous {
+                Some(holder) => warn!("Removed {} held by {}", path.display(), holder),
+                None => warn!("Removed {}", path.display()),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(LockError::Io(e)),
+        }
+        Self::new(main_pid, path)
+    }
 }
 
 impl Drop for Lockfile {
     fn drop(&mut self) {
-        debug!("Removing Lock");
-        let res = remove_file(&self.path);
-        if let Err(e) = res {
+        // The file is kept: removing it would let the next zenith lock a new file while one
+        // that opened this one before the removal still gets the lock on it.
+        debug!("Releasing Lock");
+        if let Err(e) = self.file.set_len(0) {
             error!(
-                "Error deleting lockfile: path={}, error={:?}",
+                "Error clearing lockfile: path={}, error={:?}",
                 self.path.display(),
                 e
             );
@@ -148,17 +263,33 @@ impl Drop for Lockfile {
     }
 }
 
-async fn is_zenith_running(path: &Path) -> bool {
-    name_of_process_for_pidfile(path)
-        .await
-        .is_some_and(|name| name == "zenith")
+fn try_lock(file: &File) -> io::Result<()> {
+    // SAFETY: the descriptor belongs to `file`, which is open for the duration of the call
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
 }
 
-async fn name_of_process_for_pidfile(path: &Path) -> Option<String> {
-    let data = std::fs::read_to_string(path).ok()?;
-    let pid: i32 = data.parse().ok()?;
-    let process = heim::process::get(pid).await.ok()?;
-    process.name().await.ok()
+/// Reads the holder from a lockfile. Older zenith only wrote the pid, or the pid and host.
+fn parse_lockfile(data: &str) -> Option<LockHolder> {
+    let mut lines = data.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    let mut field = || {
+        lines
+            .next()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let (host, command, started) = (field(), field(), field());
+    Some(LockHolder {
+        pid,
+        host,
+        command,
+        started,
+    })
 }
 
 pub fn percent_of(numerator: u64, denominator: u64) -> f32 {
@@ -168,3 +299,189 @@ pub fn percent_of(numerator: u64, denominator: u64) -> f32 {
         (numerator as f32 / denominator as f32) * 100.0
     }
 }
+
+/// Parses a point in time given either relative to `now`, like `-6h`, `-30m`, `-2d` or `now`,
+/// or as a local time like `2024-05-01 13:30`.
+pub fn parse_time(s: &str, now: SystemTime) -> Result<SystemTime, String> {
+    let s = s.trim();
+    if s == "now" {
+        return Ok(now);
+    }
+    if let Some(ago) = s.strip_prefix('-') {
+        let d = parse_duration(ago)?;
+        return now
+            .checked_sub(d)
+            .ok_or_else(|| format!("{s} is too far in the past"));
+    }
+    let naive = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+    })
+    .ok_or_else(|| format!("Couldn't parse time: {s}"))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(SystemTime::from)
+        .ok_or_else(|| format!("{s} doesn't exist in the local time zone"))
+}
+
+/// Parses a span of time such as `-6h`, `2024-05-01 03:00` or `-2d to -1d`. Without an end it
+/// runs to `now`.
+pub fn parse_window(s: &str, now: SystemTime) -> Result<(SystemTime, SystemTime), String> {
+    let (from, to) = match s.split_once(" to ") {
+        Some((from, to)) => (parse_time(from, now)?, parse_time(to, now)?),
+        None => (parse_time(s, now)?, now),
+    };
+    if from >= to {
+        return Err(format!("{} doesn't end after it starts", s.trim()));
+    }
+    Ok((from, to))
+}
+
+/// Parses a duration such as `90s`, `30m`, `6h`, `2d` or `1w`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (n, unit) = s.split_at(split);
+    let n: u64 = n
+        .parse()
+        .map_err(|_| format!("Couldn't parse duration: {s}"))?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        "w" => 60 * 60 * 24 * 7,
+        _ => return Err(format!("Unknown unit in duration: {s}")),
+    };
+    n.checked_mul(secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Duration is too long: {s}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_parse_lockfile() {
+        assert_eq!(
+            parse_lockfile("1234\nbuild-01\n"),
+            Some(LockHolder {
+                pid: 1234,
+                host: Some("build-01".to_string()),
+                command: None,
+                started: None
+            })
+        );
+        assert_eq!(
+            parse_lockfile("1234\nbuild-01\nzenith.base\n2026-01-02 03:04:05\n")
+                .unwrap()
+                .to_string(),
+            "zenith.base (pid 1234) on build-01 since 2026-01-02 03:04:05"
+        );
+        // written by older zenith
+        assert_eq!(
+            parse_lockfile("1234"),
+            Some(LockHolder {
+                pid: 1234,
+                host: None,
+                command: None,
+                started: None
+            })
+        );
+        assert_eq!(parse_lockfile("garbage"), None);
+    }
+
+    #[test]
+    fn test_lockfile() {
+        let dir = std::env::temp_dir().join(format!("zenith-lock-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(".zenith.lock");
+
+        // flock locks belong to the open file, so a second open in this process is refused
+        let lock = Lockfile::new(1, &path).unwrap();
+        match Lockfile::new(2, &path) {
+            Err(LockError::Held(Some(holder))) => {
+                assert_eq!(holder.pid, 1);
+                assert_eq!(holder.host, Some(hostname()));
+            }
+            _ => panic!("lock taken twice"),
+        }
+        drop(lock);
+
+        // a lock left behind is forced away, the old holder keeps a file nobody else opens
+        let stale = Lockfile::new(3, &path).unwrap();
+        let lock = Lockfile::force(4, &path).unwrap();
+        assert!(matches!(
+            Lockfile::new(5, &path),
+            Err(LockError::Held(Some(LockHolder { pid: 4, .. })))
+        ));
+        drop(stale);
+        drop(lock);
+        assert!(Lockfile::new(6, &path).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("6h"), Ok(Duration::from_secs(6 * 60 * 60)));
+        assert_eq!(
+            parse_duration("2d"),
+            Ok(Duration::from_secs(2 * 24 * 60 * 60))
+        );
+        assert!(parse_duration("6").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("6y").is_err());
+        assert!(parse_duration("99999999999999999w").is_err());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = SystemTime::now();
+        assert_eq!(parse_time("now", now), Ok(now));
+        assert_eq!(
+            parse_time("-6h", now),
+            Ok(now - Duration::from_secs(6 * 60 * 60))
+        );
+
+        let t = parse_time("2024-05-01 13:30", now).unwrap();
+        let local = DateTime::<Local>::from(t);
+        assert_eq!(
+            local.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2024-05-01 13:30:00"
+        );
+        assert_eq!(parse_time("2024-05-01T13:30", now), Ok(t));
+        assert!(parse_time("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn test_parse_window() {
+        let now = SystemTime::now();
+        let hours = |h: u64| Duration::from_secs(h * 60 * 60);
+        assert_eq!(parse_window("-6h", now), Ok((now - hours(6), now)));
+        assert_eq!(
+            parse_window("-2d to -1d", now),
+            Ok((now - hours(48), now - hours(24)))
+        );
+        let from = parse_time("2024-05-01 03:00", now).unwrap();
+        assert_eq!(
+            parse_window("2024-05-01 03:00 to 2024-05-01 05:00", now),
+            Ok((from, from + hours(2)))
+        );
+        assert!(parse_window("-1d to -2d", now).is_err());
+        assert!(parse_window("now", now).is_err());
+        assert!(parse_window("-1d to", now).is_err());
+    }
+}